@@ -0,0 +1,137 @@
+// Command line evaluator using the same engine as the calculator app.
+// Evaluates each argument as a statement, or each line of stdin when
+// no statements are given as arguments. Exits with a non-zero code
+// if any statement gives an error.
+// When stdin is a terminal (or with --repl) an interactive session
+// is started instead.
+
+use std::io::{self, BufRead, IsTerminal};
+use std::path::Path;
+use std::process::ExitCode;
+use basic_calculator::expression_evaluate::{self, Context, StatementResult};
+use basic_calculator::currency::CurrencyRates;
+use basic_calculator::numeric::NumberMode;
+mod repl;
+
+const USAGE: &str = "Usage: calc-cli [--float | --decimal | --fraction | --complex | --programmer] [--repl] [STATEMENT]...
+Evaluates each statement given (or each line of stdin when none are given)
+and prints its answer. Statements can be expressions (e.g \"2*(3+4)\"),
+variable assignments (e.g \"rate=0.07\") or function definitions
+(e.g \"f(x)=x^2\").
+
+Options:
+  --float    use floating point numbers
+  --decimal  use exact decimal numbers (default)
+  --fraction use exact fractions (e.g \"1/3*3\" is 1)
+  --complex  use complex numbers (e.g \"i^2\" is -1)
+  --programmer
+             use 64-bit integers (e.g \"0xff & ~0x0f\" is 240)
+  --repl     start an interactive session (default when stdin is a terminal)
+  --help     show this message
+
+Exchange rates for currencies (e.g \"100 USD to EUR\") are read from
+rates.csv, or the file given by the CALC_RATES_FILE environment variable.";
+
+/// Evaluates a statement, keeping answers as Ans for the following statements.
+/// 
+/// Returns the text to show for the result, or the error message
+/// (with its position in the text as typed).
+fn run_statement(text: &str, mode: NumberMode, ctx: &mut Context) -> Result<String, String> {
+    let (statement, offsets) = expression_evaluate::convert_plain_text_with_offsets(text);
+    match expression_evaluate::evaluate_statement(&statement, mode, ctx) {
+        Ok(StatementResult::Answer(answer)) => {
+            ctx.ans = answer.clone();
+            return Ok(answer);
+        }
+        Ok(StatementResult::Defined(name)) => return Ok(format!("Defined {}", name)),
+        Err(error) => {
+            let offset = offsets.get(error.offset()).copied().unwrap_or(text.chars().count());
+            return Err(error.moved_to(offset).to_string());
+        }
+    }
+}
+
+/// Runs a statement given non-interactively, printing its answer
+/// (or error).
+/// 
+/// Returns false if there was an error.
+fn run_script_statement(text: &str, mode: NumberMode, ctx: &mut Context) -> bool {
+    match run_statement(text, mode, ctx) {
+        Ok(result) => {
+            println!("{}", result);
+            return true;
+        }
+        Err(error) => {
+            eprintln!("Error in \"{}\": {}", text.trim(), error);
+            return false;
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut mode = NumberMode::Decimal;
+    let mut interactive = false;
+    let mut statements: Vec<String> = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--float" => mode = NumberMode::Float,
+            "--decimal" => mode = NumberMode::Decimal,
+            "--fraction" => mode = NumberMode::Rational,
+            "--complex" => mode = NumberMode::Complex,
+            "--programmer" => mode = NumberMode::Programmer,
+            "--repl" => interactive = true,
+            "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option \"{}\"\n\n{}", arg, USAGE);
+                return ExitCode::from(2);
+            }
+            _ => statements.push(arg)
+        }
+    }
+
+    let mut ctx = Context::new();
+    // Exchange rates are read from the rates file, when there is one
+    let rates_path = CurrencyRates::file_path();
+    if Path::new(&rates_path).exists() {
+        match CurrencyRates::load(&rates_path) {
+            Ok(rates) => ctx.currencies = rates,
+            Err(error) => eprintln!("{}", error)
+        }
+    }
+    if interactive || (statements.is_empty() && io::stdin().is_terminal()) {
+        // Statements given as arguments are run first to set up the session
+        for statement in statements.iter() {
+            let _ = run_script_statement(statement, mode, &mut ctx);
+        }
+        return repl::run(mode, ctx);
+    }
+
+    let mut all_ok = true;
+    if statements.is_empty() {
+        // Read statements line by line, skipping blank lines
+        for line in io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    eprintln!("Could not read input: {}", error);
+                    return ExitCode::from(2);
+                }
+            };
+            if !line.trim().is_empty() {
+                all_ok &= run_script_statement(&line, mode, &mut ctx);
+            }
+        }
+    } else {
+        for statement in statements.iter() {
+            all_ok &= run_script_statement(statement, mode, &mut ctx);
+        }
+    }
+
+    if all_ok {
+        return ExitCode::SUCCESS;
+    }
+    return ExitCode::FAILURE;
+}
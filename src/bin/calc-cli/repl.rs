@@ -0,0 +1,111 @@
+/// Interactive terminal session for the command line evaluator.
+/// Ans, variables, functions and history are kept between lines.
+
+use std::process::ExitCode;
+use rustyline::{DefaultEditor, error::ReadlineError};
+use basic_calculator::expression_evaluate::Context;
+use basic_calculator::numeric::NumberMode;
+use super::run_statement;
+
+const PROMPT: &str = "> ";
+// Prompt for the following lines of a statement with unclosed brackets
+const CONTINUE_PROMPT: &str = "... ";
+const HELP: &str = "Enter a statement to evaluate it, e.g \"2*(3+4)\", \"rate=0.07\" or \"f(x)=x^2\".
+Statements with unclosed brackets continue onto the next line.
+
+Commands:
+  :vars     list variables and functions
+  :history  list past calculations
+  :help     show this message
+  :quit     end the session (as does Ctrl-D)";
+
+/// Checks whether the text has more open brackets than close brackets,
+/// meaning the statement continues onto the next line.
+fn has_unclosed_bracket(text: &str) -> bool {
+    let opens = text.chars().filter(|ch| *ch == '(').count();
+    let closes = text.chars().filter(|ch| *ch == ')').count();
+    return opens > closes;
+}
+
+/// Lists the variables and functions of the context.
+fn print_variables(ctx: &Context) {
+    if ctx.variables.is_empty() && ctx.functions.is_empty() {
+        println!("No variables or functions");
+    }
+    for (name, value) in ctx.variables.iter() {
+        println!("{} = {}", name, value);
+    }
+    for (name, function) in ctx.functions.iter() {
+        println!("{}{}", name, function);
+    }
+}
+
+/// Runs the interactive session until it is quit.
+pub fn run(mode: NumberMode, mut ctx: Context) -> ExitCode {
+    let mut editor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("Could not start interactive session: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Past statements and their results (or error messages)
+    let mut past_entries: Vec<(String, String)> = Vec::new();
+    // Lines of the statement being entered
+    let mut statement = String::new();
+
+    println!("Type :help for help");
+    loop {
+        let prompt = if statement.is_empty() { PROMPT } else { CONTINUE_PROMPT };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C abandons the statement being entered
+            Err(ReadlineError::Interrupted) => {
+                statement.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(error) => {
+                eprintln!("Could not read input: {}", error);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if statement.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ":quit" | ":q" => break,
+                ":help" => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                ":vars" => {
+                    print_variables(&ctx);
+                    continue;
+                }
+                ":history" => {
+                    for (past_statement, result) in past_entries.iter() {
+                        println!("{}\n  = {}", past_statement, result);
+                    }
+                    continue;
+                }
+                _ => ()
+            }
+        } else {
+            statement.push('\n');
+        }
+        statement.push_str(&line);
+        if has_unclosed_bracket(&statement) {
+            continue;
+        }
+
+        let _ = editor.add_history_entry(statement.as_str());
+        let result = match run_statement(&statement, mode, &mut ctx) {
+            Ok(result) => result,
+            Err(error) => format!("Error: {}", error)
+        };
+        println!("{}", result);
+        past_entries.push((std::mem::take(&mut statement), result));
+    }
+    return ExitCode::SUCCESS;
+}
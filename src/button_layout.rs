@@ -7,26 +7,71 @@ use eframe::egui;
 const ORANG: egui::Color32 = egui::Color32::from_rgb(247, 137, 52);
 const WHITE: egui::Color32 = egui::Color32::WHITE;
 const LBLUE: egui::Color32 = egui::Color32::from_rgb(132, 151, 245);
+const GREEN: egui::Color32 = egui::Color32::from_rgb(110, 200, 140);
 struct ButInfo<'a>(&'a str, egui::Color32);
-const BUTTON_INFO: [ButInfo; 24] = [
-    ButInfo("C", ORANG), ButInfo("π", LBLUE), ButInfo("e", LBLUE), ButInfo("^", ORANG),
-    ButInfo("(", ORANG), ButInfo(")", ORANG), ButInfo("√", ORANG), ButInfo("÷", ORANG),
-    ButInfo("7", WHITE), ButInfo("8", WHITE), ButInfo("9", WHITE), ButInfo("×", ORANG),
-    ButInfo("4", WHITE), ButInfo("5", WHITE), ButInfo("6", WHITE), ButInfo("-", ORANG),
-    ButInfo("1", WHITE), ButInfo("2", WHITE), ButInfo("3", WHITE), ButInfo("+", ORANG),
-    ButInfo("⌫", ORANG), ButInfo("0", WHITE), ButInfo(".", WHITE), ButInfo("=", ORANG)
+// Buttons which switch to the other page of buttons
+const TO_SCIENTIFIC: &str = "f(x)";
+const TO_BASIC: &str = "123";
+const BASIC_BUTTONS: [ButInfo; 30] = [
+    ButInfo("MC", GREEN), ButInfo("C", ORANG), ButInfo("π", LBLUE), ButInfo("%", ORANG), ButInfo("^", ORANG),
+    ButInfo("MR", GREEN), ButInfo("(", ORANG), ButInfo(")", ORANG), ButInfo("√", ORANG), ButInfo("÷", ORANG),
+    ButInfo("M+", GREEN), ButInfo("7", WHITE), ButInfo("8", WHITE), ButInfo("9", WHITE), ButInfo("×", ORANG),
+    ButInfo("M-", GREEN), ButInfo("4", WHITE), ButInfo("5", WHITE), ButInfo("6", WHITE), ButInfo("-", ORANG),
+    ButInfo("Ans", LBLUE), ButInfo("1", WHITE), ButInfo("2", WHITE), ButInfo("3", WHITE), ButInfo("+", ORANG),
+    ButInfo(TO_SCIENTIFIC, WHITE), ButInfo("⌫", ORANG), ButInfo("0", WHITE), ButInfo(".", WHITE), ButInfo("=", ORANG)
+];
+// Basic page used when entering in RPN
+const RPN_BUTTONS: [ButInfo; 30] = [
+    ButInfo("MC", GREEN), ButInfo("C", ORANG), ButInfo("DROP", GREEN), ButInfo("ROLL", GREEN), ButInfo("^", ORANG),
+    ButInfo("MR", GREEN), ButInfo("SWAP", GREEN), ButInfo("±", ORANG), ButInfo("√", ORANG), ButInfo("÷", ORANG),
+    ButInfo("M+", GREEN), ButInfo("7", WHITE), ButInfo("8", WHITE), ButInfo("9", WHITE), ButInfo("×", ORANG),
+    ButInfo("M-", GREEN), ButInfo("4", WHITE), ButInfo("5", WHITE), ButInfo("6", WHITE), ButInfo("-", ORANG),
+    ButInfo("π", LBLUE), ButInfo("1", WHITE), ButInfo("2", WHITE), ButInfo("3", WHITE), ButInfo("+", ORANG),
+    ButInfo(TO_SCIENTIFIC, WHITE), ButInfo("⌫", ORANG), ButInfo("0", WHITE), ButInfo(".", WHITE), ButInfo("ENTER", ORANG)
+];
+const SCIENTIFIC_BUTTONS: [ButInfo; 30] = [
+    ButInfo("sin", ORANG), ButInfo("cos", ORANG), ButInfo("tan", ORANG), ButInfo("(", ORANG), ButInfo(")", ORANG),
+    ButInfo("asin", ORANG), ButInfo("acos", ORANG), ButInfo("atan", ORANG), ButInfo(",", ORANG), ButInfo("!", ORANG),
+    ButInfo("sinh", ORANG), ButInfo("cosh", ORANG), ButInfo("tanh", ORANG), ButInfo("^", ORANG), ButInfo("√", ORANG),
+    ButInfo("asinh", ORANG), ButInfo("acosh", ORANG), ButInfo("atanh", ORANG), ButInfo("e", LBLUE), ButInfo("DRG", GREEN),
+    ButInfo("ln", ORANG), ButInfo("log10", ORANG), ButInfo("log", ORANG), ButInfo("exp", ORANG), ButInfo("abs", ORANG),
+    ButInfo(TO_BASIC, WHITE), ButInfo("floor", ORANG), ButInfo("ceil", ORANG), ButInfo("round", ORANG), ButInfo("⌫", ORANG)
+];
+// Function page used in programmer mode, with hex digits,
+// base prefixes and bitwise operators
+const PROGRAMMER_BUTTONS: [ButInfo; 30] = [
+    ButInfo("0x", LBLUE), ButInfo("0o", LBLUE), ButInfo("0b", LBLUE), ButInfo("(", ORANG), ButInfo(")", ORANG),
+    ButInfo("a", WHITE), ButInfo("b", WHITE), ButInfo("c", WHITE), ButInfo("d", WHITE), ButInfo("e", WHITE),
+    ButInfo("f", WHITE), ButInfo("¬", ORANG), ButInfo("&", ORANG), ButInfo("|", ORANG), ButInfo("⊕", ORANG),
+    ButInfo("«", ORANG), ButInfo("»", ORANG), ButInfo("mod", ORANG), ButInfo("^", ORANG), ButInfo("!", ORANG),
+    ButInfo("Ans", LBLUE), ButInfo("abs", ORANG), ButInfo("√", ORANG), ButInfo("÷", ORANG), ButInfo("×", ORANG),
+    ButInfo(TO_BASIC, WHITE), ButInfo("C", ORANG), ButInfo("-", ORANG), ButInfo("+", ORANG), ButInfo("⌫", ORANG)
 ];
 // Grid formating consts
-const N_COL: usize = 4;
+const N_COL: usize = 5;
 const N_ROW: usize = 6;
 const SPACING: egui::Vec2 = egui::Vec2 {x: 2.0, y: 2.0};
 const FONT_SIZE: f32 = 32.0; // Font size for the text on each button
+const SMALL_FONT_SIZE: f32 = 22.0; // Font size for buttons with 2-3 chars
+const TINY_FONT_SIZE: f32 = 16.0; // Font size for buttons with more chars
+
+// The pages of buttons that can be shown
+#[derive(Clone, Copy, PartialEq)]
+pub enum ButtonPage {
+    Basic,
+    Scientific
+}
 
 // Minimum ui width needs for show_buttons method to work properly
-pub const MIN_WIDTH_NEEDED: f32 = 170.0;
+pub const MIN_WIDTH_NEEDED: f32 = 215.0;
 
 pub struct CalculatorButtons {
     pub recent_press: Option<String>,
+    pub page: ButtonPage,
+    // Whether the basic page has the RPN buttons
+    pub rpn_entry: bool,
+    // Whether the function page has the programmer buttons
+    pub programmer: bool,
     pub height: f32,
     pub width: f32
 }
@@ -34,6 +79,9 @@ impl CalculatorButtons {
     pub fn new() -> Self {
         return CalculatorButtons {
             recent_press: None,
+            page: ButtonPage::Basic,
+            rpn_entry: false,
+            programmer: false,
             height: 0.0,
             width: 0.0
         }
@@ -47,6 +95,12 @@ impl CalculatorButtons {
         ui.set_width(self.width);
 
         self.recent_press = None;
+        let buttons: &[ButInfo] = match self.page {
+            ButtonPage::Basic if self.rpn_entry => &RPN_BUTTONS,
+            ButtonPage::Basic => &BASIC_BUTTONS,
+            ButtonPage::Scientific if self.programmer => &PROGRAMMER_BUTTONS,
+            ButtonPage::Scientific => &SCIENTIFIC_BUTTONS
+        };
         let button_width = (self.width - ((N_COL-1) as f32)*SPACING.x) / N_COL as f32;
         // Formatting button grid (sizing and spacing)
         let button_grid = egui::Grid::new("Stuff")
@@ -60,15 +114,26 @@ impl CalculatorButtons {
             for i in 0..N_ROW {
                 for j in 0..N_COL {
                     // Calculate index
-                    let index: usize = (i*N_COL + j) as usize;
+                    let index: usize = i*N_COL + j;
+                    // Shrink text which won't fit at the usual size
+                    let font_size = match buttons[index].0.chars().count() {
+                        1 => FONT_SIZE,
+                        2..=3 => SMALL_FONT_SIZE,
+                        _ => TINY_FONT_SIZE
+                    };
                     // Create button and check if its clicked
                     ui.centered_and_justified(|ui| {
                         if ui.add(
                             egui::Button::new(
-                                egui::RichText::new(BUTTON_INFO[index].0).size(FONT_SIZE).color(BUTTON_INFO[index].1))
+                                egui::RichText::new(buttons[index].0).size(font_size).color(buttons[index].1))
                             .fill(egui::color::Color32::TRANSPARENT))
                         .clicked() {
-                            self.recent_press = Some(BUTTON_INFO[index].0.to_string());
+                            // Page switches are handled here rather than as a press
+                            match buttons[index].0 {
+                                TO_SCIENTIFIC => self.page = ButtonPage::Scientific,
+                                TO_BASIC => self.page = ButtonPage::Basic,
+                                text => self.recent_press = Some(text.to_string())
+                            }
                         }
                     });
                 }
@@ -0,0 +1,139 @@
+/// Exchange rates for converting between currencies (e.g "100 USD to EUR").
+/// Rates are read from a local CSV file the user can edit, so
+/// conversions work without an internet connection.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+// File the rates are read from, unless set by RATES_FILE_VARIABLE
+pub const DEFAULT_RATES_FILE: &str = "rates.csv";
+// Environment variable giving the path of the rates file
+pub const RATES_FILE_VARIABLE: &str = "CALC_RATES_FILE";
+
+/// Reasons a rates file could not be read. Line numbers start from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RatesError {
+    // The file could not be opened, holds the reason given
+    Unreadable(String),
+    // A line isn't in the form "CODE,rate" or "updated,date"
+    MalformedLine(usize),
+    // A currency code isn't three capital letters e.g "EUR"
+    InvalidCode(usize),
+    // A rate isn't a positive number written with digits and a point
+    InvalidRate(usize),
+    // A currency is given more than once
+    DuplicateCode(usize),
+    // No currencies were given
+    Empty
+}
+impl fmt::Display for RatesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RatesError::Unreadable(reason) => return write!(f, "Could not read rates file: {}", reason),
+            RatesError::MalformedLine(line) => return write!(f, "Malformed rate on line {}", line),
+            RatesError::InvalidCode(line) => return write!(f, "Invalid currency code on line {}", line),
+            RatesError::InvalidRate(line) => return write!(f, "Invalid rate on line {}", line),
+            RatesError::DuplicateCode(line) => return write!(f, "Repeated currency on line {}", line),
+            RatesError::Empty => return write!(f, "No rates given")
+        }
+    }
+}
+
+/// Checks text is a number written as plainly as the evaluator reads
+/// it: digits with at most one decimal point (e.g "0.92" but not "1e3").
+fn is_plain_decimal(text: &str) -> bool {
+    return text.chars().all(|ch| ch.is_ascii_digit() || ch == '.')
+        && text.chars().filter(|ch| *ch == '.').count() <= 1
+        && text.chars().any(|ch| ch.is_ascii_digit());
+}
+
+/// Exchange rates, given as how much of each currency one of the
+/// base currency (the first in the file) is worth e.g
+/// ```text
+/// # comments start with a #
+/// updated,2026-10-01
+/// USD,1
+/// EUR,0.92
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurrencyRates {
+    // When the rates were last updated, as written in the file
+    pub updated: Option<String>,
+    // Currency the others are measured against
+    pub base: Option<String>,
+    // Rate of each currency in text form, so each Numeric
+    // type can read as many digits as it can hold
+    rates: BTreeMap<String, String>
+}
+
+impl CurrencyRates {
+    /// Rates with no currencies
+    pub fn new() -> Self {
+        return Self {
+            updated: None,
+            base: None,
+            rates: BTreeMap::new()
+        };
+    }
+
+    /// Reads rates from the text of a rates file.
+    pub fn parse(text: &str) -> Result<Self, RatesError> {
+        let mut rates = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (code, value) = line.split_once(',').ok_or(RatesError::MalformedLine(line_number))?;
+            let (code, value) = (code.trim(), value.trim());
+            if code == "updated" {
+                rates.updated = Some(String::from(value));
+                continue;
+            }
+            if code.len() != 3 || !code.chars().all(|ch| ch.is_ascii_uppercase()) {
+                return Err(RatesError::InvalidCode(line_number));
+            }
+            if !is_plain_decimal(value) || !value.parse::<f64>().is_ok_and(|rate| rate > 0.0) {
+                return Err(RatesError::InvalidRate(line_number));
+            }
+            if rates.rates.insert(String::from(code), String::from(value)).is_some() {
+                return Err(RatesError::DuplicateCode(line_number));
+            }
+            if rates.base.is_none() {
+                rates.base = Some(String::from(code));
+            }
+        }
+        if rates.rates.is_empty() {
+            return Err(RatesError::Empty);
+        }
+        return Ok(rates);
+    }
+
+    /// Reads rates from the file at the given path.
+    pub fn load(path: &str) -> Result<Self, RatesError> {
+        let text = std::fs::read_to_string(path).map_err(|error| RatesError::Unreadable(error.to_string()))?;
+        return Self::parse(&text);
+    }
+
+    /// Path of the rates file, which can be set with the
+    /// CALC_RATES_FILE environment variable.
+    pub fn file_path() -> String {
+        return std::env::var(RATES_FILE_VARIABLE).unwrap_or(String::from(DEFAULT_RATES_FILE));
+    }
+
+    /// Worth of one of the given currency in the base currency, as an
+    /// expression e.g "1÷0.92" for EUR.
+    ///
+    /// Returns None if the currency has no rate.
+    pub fn scale(&self, code: &str) -> Option<String> {
+        let rate = self.rates.get(code)?;
+        let base_rate = self.rates.get(self.base.as_ref()?)?;
+        return Some(format!("{}÷{}", base_rate, rate));
+    }
+}
+impl Default for CurrencyRates {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
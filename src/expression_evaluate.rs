@@ -1,55 +1,368 @@
 /// Code for the evaluation of infix expressions
 /// (by using postfix notation aka Reverse Polish Notation).
 
+use std::fmt;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::numeric::{Numeric, NumberMode, decimal::Decimal, rational::Rational, complex::Complex, word::WordSize};
+use crate::with_word_type;
+use crate::units::{self, Dimension, Quantity};
+use crate::currency::CurrencyRates;
+
+/// Reasons an expression could fail to evaluate. Each variant holds
+/// the char offset (starting from 0) into the infix expression where
+/// the problem was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    UnbalancedBracket(usize),
+    MissingOperand(usize),
+    MalformedNumber(usize),
+    UnknownSymbol(usize),
+    DomainError(usize),
+    InvalidAssignment(usize),
+    ArgumentCount(usize),
+    RecursionLimit(usize),
+    MissingOperator(usize),
+    DimensionMismatch(usize)
+}
+impl EvalError {
+    /// Char offset into the infix expression where the error occurred
+    pub fn offset(&self) -> usize {
+        match self {
+            EvalError::UnbalancedBracket(offset)
+            | EvalError::MissingOperand(offset)
+            | EvalError::MalformedNumber(offset)
+            | EvalError::UnknownSymbol(offset)
+            | EvalError::DomainError(offset)
+            | EvalError::InvalidAssignment(offset)
+            | EvalError::ArgumentCount(offset)
+            | EvalError::RecursionLimit(offset)
+            | EvalError::MissingOperator(offset)
+            | EvalError::DimensionMismatch(offset) => return *offset
+        }
+    }
+
+    /// Describes the error (without its position)
+    pub fn description(&self) -> &'static str {
+        match self {
+            EvalError::UnbalancedBracket(_) => return "Unbalanced bracket",
+            EvalError::MissingOperand(_) => return "Missing operand",
+            EvalError::MalformedNumber(_) => return "Malformed number",
+            EvalError::UnknownSymbol(_) => return "Unknown symbol",
+            EvalError::DomainError(_) => return "Domain error",
+            EvalError::InvalidAssignment(_) => return "Invalid assignment",
+            EvalError::ArgumentCount(_) => return "Wrong number of inputs to function",
+            EvalError::RecursionLimit(_) => return "Too many nested function calls",
+            EvalError::MissingOperator(_) => return "Missing operator",
+            EvalError::DimensionMismatch(_) => return "Units don't match"
+        }
+    }
+
+    /// Gives the same error but at a different offset
+    pub fn moved_to(self, offset: usize) -> EvalError {
+        match self {
+            EvalError::UnbalancedBracket(_) => return EvalError::UnbalancedBracket(offset),
+            EvalError::MissingOperand(_) => return EvalError::MissingOperand(offset),
+            EvalError::MalformedNumber(_) => return EvalError::MalformedNumber(offset),
+            EvalError::UnknownSymbol(_) => return EvalError::UnknownSymbol(offset),
+            EvalError::DomainError(_) => return EvalError::DomainError(offset),
+            EvalError::InvalidAssignment(_) => return EvalError::InvalidAssignment(offset),
+            EvalError::ArgumentCount(_) => return EvalError::ArgumentCount(offset),
+            EvalError::RecursionLimit(_) => return EvalError::RecursionLimit(offset),
+            EvalError::MissingOperator(_) => return EvalError::MissingOperator(offset),
+            EvalError::DimensionMismatch(_) => return EvalError::DimensionMismatch(offset)
+        }
+    }
+
+    /// Moves the offset of the error along by the given amount.
+    /// Used when evaluating an expression that is part of a larger one.
+    fn shift(self, amount: usize) -> EvalError {
+        let offset = self.offset() + amount;
+        return self.moved_to(offset);
+    }
+}
+impl fmt::Display for EvalError {
+    // Positions are shown starting from 1 as that is how people count chars
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{} at position {}", self.description(), self.offset() + 1);
+    }
+}
+
 // enums for brackets
-#[derive(Clone)]
-enum Bracket {
+#[derive(Clone, Debug, PartialEq)]
+pub enum Bracket {
     Open,
     Close
 }
 // enums for single input operators
-#[derive(Clone)]
-enum OneInOperator {
+#[derive(Clone, Debug, PartialEq)]
+pub enum OneInOperator {
     SquareRoot,
-    Negative
+    Negative,
+    Factorial,
+    Sin,
+    Cos,
+    Tan,
+    ArcSin,
+    ArcCos,
+    ArcTan,
+    Sinh,
+    Cosh,
+    Tanh,
+    ArcSinh,
+    ArcCosh,
+    ArcTanh,
+    Ln,
+    Log10,
+    Exp,
+    Abs,
+    Floor,
+    Ceil,
+    Round,
+    // Bitwise NOT, flipping every bit of an integer
+    Not
 }
 impl OneInOperator {
-    fn apply(&self, num: f64) -> f64 {
+    /// Returns None if the input is outside the operator's domain.
+    /// Angles are taken and given in the angle mode given.
+    pub fn apply<N: Numeric>(&self, num: N, angle_mode: AngleMode) -> Option<N> {
+        match self {
+            OneInOperator::SquareRoot => return num.square_root(),
+            OneInOperator::Negative => return num.negative(),
+            OneInOperator::Factorial => return num.factorial(),
+            OneInOperator::Sin|OneInOperator::Cos|OneInOperator::Tan => return self.apply_trig(num, angle_mode),
+            OneInOperator::ArcSin => return num.map_f64(|x| angle_mode.convert_from_radians(x.asin())),
+            OneInOperator::ArcCos => return num.map_f64(|x| angle_mode.convert_from_radians(x.acos())),
+            OneInOperator::ArcTan => return num.map_f64(|x| angle_mode.convert_from_radians(x.atan())),
+            OneInOperator::Sinh => return num.sinh(),
+            OneInOperator::Cosh => return num.cosh(),
+            OneInOperator::Tanh => return num.tanh(),
+            OneInOperator::ArcSinh => return num.map_f64(f64::asinh),
+            OneInOperator::ArcCosh => return num.map_f64(f64::acosh),
+            OneInOperator::ArcTanh => return num.map_f64(f64::atanh),
+            OneInOperator::Ln => return num.ln(),
+            OneInOperator::Log10 => return num.log10(),
+            OneInOperator::Exp => return num.exp(),
+            OneInOperator::Abs => return num.abs(),
+            OneInOperator::Floor => return num.floor(),
+            OneInOperator::Ceil => return num.ceil(),
+            OneInOperator::Round => return num.round(),
+            OneInOperator::Not => return num.bit_not()
+        }
+    }
+
+    /// Applies sin, cos or tan to an angle in the given angle mode.
+    /// Outside of radians, angles which are a whole number of quarter
+    /// turns give exact answers (e.g sin(180°) is 0 rather than 1.2e-16)
+    fn apply_trig<N: Numeric>(&self, num: N, angle_mode: AngleMode) -> Option<N> {
+        let angle = match num.to_f64() {
+            Some(angle) => angle,
+            // angles which aren't real (e.g complex) are converted
+            // to radians by multiplying
+            None => {
+                let to_radians = N::from_f64(angle_mode.convert_to_radians(1.0))?;
+                let radians = num.multiply(&to_radians)?;
+                match self {
+                    OneInOperator::Sin => return radians.sin(),
+                    OneInOperator::Cos => return radians.cos(),
+                    _ => return radians.tan()
+                }
+            }
+        };
+        let quarter_turns = angle * 4.0 / angle_mode.full_turn();
+        if angle_mode != AngleMode::Radians && quarter_turns.fract() == 0.0 {
+            // position on the unit circle (0 to 3 quarter turns)
+            let quadrant = quarter_turns.rem_euclid(4.0) as usize;
+            let exact = match self {
+                OneInOperator::Sin => ["0", "1", "0", "-1"][quadrant],
+                OneInOperator::Cos => ["1", "0", "-1", "0"][quadrant],
+                // tan is undefined at 90° and 270°
+                _ => ["0", "", "0", ""][quadrant]
+            };
+            return N::parse_literal(exact);
+        }
+        let radians = angle_mode.convert_to_radians(angle);
         match self {
-            OneInOperator::SquareRoot => return num.sqrt(),
-            OneInOperator::Negative => return -1.0 * num
+            OneInOperator::Sin => return num.map_f64(|_| radians.sin()),
+            OneInOperator::Cos => return num.map_f64(|_| radians.cos()),
+            _ => return num.map_f64(|_| radians.tan())
         }
     }
 }
 // enums for two input operators
-#[derive(Clone)]
-enum TwoInOperator {
+#[derive(Clone, Debug, PartialEq)]
+pub enum TwoInOperator {
     Add,
     Subtract,
     Multiply,
     Divide,
     Power,
+    // Written as a function, log(base, number)
+    Log,
+    // Remainder of a division, written as "mod"
+    Modulo,
+    // Bitwise operations on integers
+    And,
+    Or,
+    Xor,
+    ShiftLeft,
+    ShiftRight
 }
 impl TwoInOperator {
-    fn apply(&self, left_num: f64, right_num: f64) -> f64 {
+    /// Whether a chain of the operator is evaluated from the right
+    /// e.g 2^3^2 = 2^(3^2)
+    pub fn is_right_associative(&self) -> bool {
+        return matches!(self, TwoInOperator::Power);
+    }
+
+    /// Returns None if the inputs are outside the operator's domain
+    pub fn apply<N: Numeric>(&self, left_num: N, right_num: N) -> Option<N> {
         match self {
-            TwoInOperator::Add => return left_num + right_num,
-            TwoInOperator::Subtract => return left_num - right_num,
-            TwoInOperator::Multiply => return left_num * right_num,
-            TwoInOperator::Divide => return left_num / right_num,
-            TwoInOperator::Power => return left_num.powf(right_num)
+            TwoInOperator::Add => return left_num.add(&right_num),
+            TwoInOperator::Subtract => return left_num.subtract(&right_num),
+            TwoInOperator::Multiply => return left_num.multiply(&right_num),
+            TwoInOperator::Divide => return left_num.divide(&right_num),
+            TwoInOperator::Power => return left_num.power(&right_num),
+            TwoInOperator::Log => return right_num.log(&left_num),
+            TwoInOperator::Modulo => return left_num.modulo(&right_num),
+            TwoInOperator::And => return left_num.bit_and(&right_num),
+            TwoInOperator::Or => return left_num.bit_or(&right_num),
+            TwoInOperator::Xor => return left_num.bit_xor(&right_num),
+            TwoInOperator::ShiftLeft => return left_num.shift_left(&right_num),
+            TwoInOperator::ShiftRight => return left_num.shift_right(&right_num)
         }
     }
 }
-#[derive(Clone)]
-enum Operator {
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operator {
     OneInOperator(OneInOperator),
     TwoInOperator(TwoInOperator),
-    Bracket(Bracket)
+    Bracket(Bracket),
+    // Separates the inputs of a function e.g the "," in log(2,8)
+    Separator,
+    // Percentage of what it follows e.g 50% = 0.5, except as the right
+    // input of an add or subtract where it is a percentage of the left
+    // input e.g 200+10% = 220
+    Percent,
+    // Call to a user defined function, holds its name and number of inputs
+    UserFunction(String, usize),
+    // Converts everything before it into the units after it,
+    // written as "to" or "in" e.g 5 km to mi
+    Convert
+}
+impl Operator {
+    /// Number of inputs taken by operators written as functions (e.g sin(x)).
+    /// 
+    /// Returns None for operators not written as functions.
+    pub(crate) fn function_arity(&self) -> Option<usize> {
+        match self {
+            Operator::OneInOperator(OneInOperator::SquareRoot)
+            | Operator::OneInOperator(OneInOperator::Negative)
+            | Operator::OneInOperator(OneInOperator::Factorial)
+            | Operator::OneInOperator(OneInOperator::Not) => return None,
+            Operator::OneInOperator(_) => return Some(1),
+            Operator::TwoInOperator(TwoInOperator::Log) => return Some(2),
+            Operator::UserFunction(_, arity) => return Some(*arity),
+            _ => return None
+        }
+    }
+}
+
+impl fmt::Display for OneInOperator {
+    // Shown as written in expressions, except negatives which are
+    // shown as "neg" to tell them apart from subtraction
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            OneInOperator::SquareRoot => "√",
+            OneInOperator::Negative => "neg",
+            OneInOperator::Factorial => "!",
+            OneInOperator::Sin => "sin",
+            OneInOperator::Cos => "cos",
+            OneInOperator::Tan => "tan",
+            OneInOperator::ArcSin => "asin",
+            OneInOperator::ArcCos => "acos",
+            OneInOperator::ArcTan => "atan",
+            OneInOperator::Sinh => "sinh",
+            OneInOperator::Cosh => "cosh",
+            OneInOperator::Tanh => "tanh",
+            OneInOperator::ArcSinh => "asinh",
+            OneInOperator::ArcCosh => "acosh",
+            OneInOperator::ArcTanh => "atanh",
+            OneInOperator::Ln => "ln",
+            OneInOperator::Log10 => "log10",
+            OneInOperator::Exp => "exp",
+            OneInOperator::Abs => "abs",
+            OneInOperator::Floor => "floor",
+            OneInOperator::Ceil => "ceil",
+            OneInOperator::Round => "round",
+            OneInOperator::Not => "¬"
+        };
+        return write!(f, "{}", symbol);
+    }
 }
-enum Token {
+impl fmt::Display for TwoInOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = match self {
+            TwoInOperator::Add => "+",
+            TwoInOperator::Subtract => "-",
+            TwoInOperator::Multiply => "×",
+            TwoInOperator::Divide => "÷",
+            TwoInOperator::Power => "^",
+            TwoInOperator::Log => "log",
+            TwoInOperator::Modulo => "mod",
+            TwoInOperator::And => "&",
+            TwoInOperator::Or => "|",
+            TwoInOperator::Xor => "⊕",
+            TwoInOperator::ShiftLeft => "«",
+            TwoInOperator::ShiftRight => "»"
+        };
+        return write!(f, "{}", symbol);
+    }
+}
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operator::OneInOperator(inside) => return write!(f, "{}", inside),
+            Operator::TwoInOperator(inside) => return write!(f, "{}", inside),
+            Operator::Bracket(Bracket::Open) => return write!(f, "("),
+            Operator::Bracket(Bracket::Close) => return write!(f, ")"),
+            Operator::Separator => return write!(f, ","),
+            Operator::Percent => return write!(f, "%"),
+            Operator::UserFunction(name, _) => return write!(f, "{}", name),
+            Operator::Convert => return write!(f, "to")
+        }
+    }
+}
+
+// A function call whose inputs are being read
+struct FunctionCall {
+    arity: usize,
+    // char offset of the function's name
+    offset: usize,
+    // number of inputs found so far
+    inputs: usize
+}
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token<N: Numeric> {
     Operator(Operator),
-    Number(f64)
+    Number(N),
+    // Number with units, in base units e.g 1000 m for 1 km
+    Quantity(N, Dimension)
+}
+impl<N: Numeric> fmt::Display for Token<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Operator(op) => return write!(f, "{}", op),
+            Token::Number(num) => return write!(f, "{}", num),
+            Token::Quantity(num, dimension) => return write!(f, "{} {}", num, dimension)
+        }
+    }
+}
+// A Token along with the char offset it was read from
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionedToken<N: Numeric> {
+    pub token: Token<N>,
+    pub offset: usize
 }
 
 /// Converts a char to an associated Operator.
@@ -63,47 +376,362 @@ fn char_to_operator(ch: &char) -> Option<Operator> {
         '÷' => return Some(Operator::TwoInOperator(TwoInOperator::Divide)),
         '^' => return Some(Operator::TwoInOperator(TwoInOperator::Power)),
         '√' => return Some(Operator::OneInOperator(OneInOperator::SquareRoot)),
+        '!' => return Some(Operator::OneInOperator(OneInOperator::Factorial)),
+        '¬' => return Some(Operator::OneInOperator(OneInOperator::Not)),
+        '&' => return Some(Operator::TwoInOperator(TwoInOperator::And)),
+        '|' => return Some(Operator::TwoInOperator(TwoInOperator::Or)),
+        '⊕' => return Some(Operator::TwoInOperator(TwoInOperator::Xor)),
+        '«' => return Some(Operator::TwoInOperator(TwoInOperator::ShiftLeft)),
+        '»' => return Some(Operator::TwoInOperator(TwoInOperator::ShiftRight)),
         '(' => return Some(Operator::Bracket(Bracket::Open)),
         ')' => return Some(Operator::Bracket(Bracket::Close)),
+        ',' => return Some(Operator::Separator),
+        '%' => return Some(Operator::Percent),
         _ => return None
     }
 }
 
-/// Converts a char to an associated constant value.
+// Names of functions which span multiple chars
+pub const FUNCTION_NAMES: [&str; 20] = [
+    "sin", "cos", "tan", "asin", "acos", "atan",
+    "sinh", "cosh", "tanh", "asinh", "acosh", "atanh",
+    "ln", "log10", "log", "exp", "abs", "floor", "ceil", "round"
+];
+
+// Names of operators written between their inputs e.g 7mod3
+pub const OPERATOR_NAMES: [&str; 4] = ["mod", "xor", "to", "in"];
+
+/// Converts a function or operator name (from FUNCTION_NAMES or
+/// OPERATOR_NAMES) to an associated Operator.
+/// 
+/// Returns None if no associated Operator is found.
+fn name_to_operator(name: &str) -> Option<Operator> {
+    let one_in = match name {
+        "sin" => OneInOperator::Sin,
+        "cos" => OneInOperator::Cos,
+        "tan" => OneInOperator::Tan,
+        "asin" => OneInOperator::ArcSin,
+        "acos" => OneInOperator::ArcCos,
+        "atan" => OneInOperator::ArcTan,
+        "sinh" => OneInOperator::Sinh,
+        "cosh" => OneInOperator::Cosh,
+        "tanh" => OneInOperator::Tanh,
+        "asinh" => OneInOperator::ArcSinh,
+        "acosh" => OneInOperator::ArcCosh,
+        "atanh" => OneInOperator::ArcTanh,
+        "ln" => OneInOperator::Ln,
+        "log10" => OneInOperator::Log10,
+        "exp" => OneInOperator::Exp,
+        "abs" => OneInOperator::Abs,
+        "floor" => OneInOperator::Floor,
+        "ceil" => OneInOperator::Ceil,
+        "round" => OneInOperator::Round,
+        "log" => return Some(Operator::TwoInOperator(TwoInOperator::Log)),
+        "mod" => return Some(Operator::TwoInOperator(TwoInOperator::Modulo)),
+        "xor" => return Some(Operator::TwoInOperator(TwoInOperator::Xor)),
+        "to" | "in" => return Some(Operator::Convert),
+        _ => return None
+    };
+    return Some(Operator::OneInOperator(one_in));
+}
+
+/// Converts the symbol of an operator or built in function (e.g "+" or
+/// "sin") to its associated Operator.
+/// 
+/// Returns None if no associated Operator is found.
+pub fn operator_from_symbol(symbol: &str) -> Option<Operator> {
+    if let Some(op) = name_to_operator(symbol) {
+        return Some(op);
+    }
+    let mut chars = symbol.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => return char_to_operator(&ch),
+        _ => return None
+    }
+}
+
+/// Converts a char to the digits of an associated constant value.
+/// Digits are given as text so each Numeric type can parse
+/// as many as it can hold (or none at all, e.g i is only
+/// parsed by complex numbers).
 /// 
 /// Returns None if no associated value is found.
-fn char_to_value(ch: &char) -> Option<f64> {
+fn char_to_value(ch: &char) -> Option<&'static str> {
     match ch {
-        'e' => return Some(std::f64::consts::E),
-        'π' => return Some(std::f64::consts::PI),
+        'e' => return Some("2.71828182845904523536028747135266249775724709369995"),
+        'π' => return Some("3.14159265358979323846264338327950288419716939937510"),
+        'i' => return Some("i"),
         _ => return None
     }
 }
 
-/// Class for managing a stack of Operators
+// Names of values which span multiple chars
+pub const VALUE_NAMES: [&str; 1] = ["Ans"];
+// Deepest that calls to user defined functions can be nested
+const MAX_CALL_DEPTH: usize = 32;
+
+/// A function defined by the user e.g f(x,y)=x^2+y
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserFunction {
+    pub parameters: Vec<String>,
+    pub body: String
+}
+impl fmt::Display for UserFunction {
+    // Shown as it would be written (without the name)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "({})={}", self.parameters.join(","), self.body);
+    }
+}
+
+/// Units that angles can be measured in
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AngleMode {
+    Degrees,
+    Radians,
+    Gradians
+}
+impl AngleMode {
+    /// Size of a full turn in the unit
+    fn full_turn(&self) -> f64 {
+        match self {
+            AngleMode::Degrees => return 360.0,
+            AngleMode::Radians => return std::f64::consts::TAU,
+            AngleMode::Gradians => return 400.0
+        }
+    }
+    fn convert_to_radians(&self, angle: f64) -> f64 {
+        return angle / self.full_turn() * std::f64::consts::TAU;
+    }
+    pub fn convert_from_radians(&self, angle: f64) -> f64 {
+        return angle / std::f64::consts::TAU * self.full_turn();
+    }
+
+    /// The mode after this one when cycling through modes (DRG order)
+    pub fn next(&self) -> AngleMode {
+        match self {
+            AngleMode::Degrees => return AngleMode::Radians,
+            AngleMode::Radians => return AngleMode::Gradians,
+            AngleMode::Gradians => return AngleMode::Degrees
+        }
+    }
+    /// Short name of the mode to show to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            AngleMode::Degrees => return "DEG",
+            AngleMode::Radians => return "RAD",
+            AngleMode::Gradians => return "GRAD"
+        }
+    }
+}
+
+/// How a negative sign before a power is read
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum NegationConvention {
+    // -2^2 = -(2^2) = -4, as usually written in maths
+    Mathematical,
+    // -2^2 = (-2)^2 = 4, as on some calculators
+    Calculator
+}
+impl NegationConvention {
+    pub const ALL: [NegationConvention; 2] = [NegationConvention::Mathematical, NegationConvention::Calculator];
+
+    /// Example of the convention to show to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            NegationConvention::Mathematical => return "-x^y = -(x^y)",
+            NegationConvention::Calculator => return "-x^y = (-x)^y"
+        }
+    }
+}
+
+/// Values an expression can refer to besides those written within it,
+/// along with settings for how it is evaluated.
+/// Values are kept in text form so they can be read by any Numeric type.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Context {
+    // Answer to the previous calculation
+    pub ans: String,
+    // Unit of angles taken and given by trig functions
+    pub angle_mode: AngleMode,
+    // Whether negatives apply before or after powers
+    pub negation: NegationConvention,
+    // Values assigned to variable names e.g rate=0.07
+    pub variables: BTreeMap<String, String>,
+    // Functions defined by the user
+    pub functions: BTreeMap<String, UserFunction>,
+    // Size of the integers used in programmer mode
+    pub word_size: WordSize,
+    // Exchange rates for currencies, read from the rates file
+    // each time rather than saved
+    #[serde(skip)]
+    pub currencies: CurrencyRates,
+    // How many user defined function calls deep the evaluation is
+    #[serde(skip)]
+    call_depth: usize
+}
+impl Context {
+    pub fn new() -> Self {
+        return Self {
+            ans: String::from("0"),
+            angle_mode: AngleMode::Degrees,
+            negation: NegationConvention::Mathematical,
+            variables: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            word_size: WordSize::new(),
+            currencies: CurrencyRates::new(),
+            call_depth: 0
+        };
+    }
+}
+impl Default for Context {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Reads a value in text form as a Numeric type.
+/// Text that isn't a plain number (e.g an answer from a different
+/// number mode) is evaluated as an expression instead.
+pub fn text_to_number<N: Numeric>(text: &str) -> Option<N> {
+    return text_to_quantity(text, &Context::new()).map(|(value, _)| value);
+}
+
+/// Converts text into a number along with its dimension (in base
+/// units). Text with units (e.g "5 km") is evaluated as an expression,
+/// using the exchange rates of the context for currencies.
+fn text_to_quantity<N: Numeric>(text: &str, ctx: &Context) -> Option<(N, Dimension)> {
+    if let Some(num) = N::parse_literal(text) {
+        return Some((num, Dimension::NONE));
+    }
+    let rates_ctx = Context{currencies: ctx.currencies.clone(), ..Context::new()};
+    let answer = evaluate_quantity::<N>(text, &rates_ctx).ok()?;
+    if answer.unit.is_some() {
+        // converted answers are read back in base units
+        return text_to_quantity(&answer.to_string(), ctx);
+    }
+    return Some((answer.value, answer.dimension));
+}
+
+/// Checks if a char can start a name (of a function, value or variable).
+/// Names can contain digits after their first char.
+fn is_name_char(ch: char) -> bool {
+    return ch.is_ascii_alphabetic() || ch == '_';
+}
+
+/// Finds which of the given names appears in chars at the given offset.
+/// The longest name is taken if several match (e.g "sinh" over "sin").
+/// Names must not be directly followed by more letters
+/// (e.g "login" doesn't contain "log").
+/// 
+/// Returns None if no name is found.
+fn match_name<'a>(chars: &[char], offset: usize, names: &[&'a str]) -> Option<&'a str> {
+    let mut found: Option<&'a str> = None;
+    for name in names {
+        let name_chars: Vec<char> = name.chars().collect();
+        let name_end = offset + name_chars.len();
+        if chars[offset..].starts_with(&name_chars)
+            && !chars.get(name_end).is_some_and(|ch| is_name_char(*ch))
+            && found.is_none_or(|longest| longest.len() < name.len()) {
+            found = Some(name);
+        }
+    }
+    return found;
+}
+
+/// Reads the name (of a value or variable) starting at the given offset.
+fn read_name(chars: &[char], offset: usize) -> String {
+    return chars[offset..].iter()
+        .take_while(|ch| is_name_char(**ch) || ch.is_ascii_digit())
+        .collect();
+}
+
+/// Checks if text can be used as the name of a variable. Names
+/// already used by functions, values and constants are not allowed.
+pub fn is_valid_variable_name(name: &str) -> bool {
+    let chars: Vec<char> = name.chars().collect();
+    if chars.is_empty() || !is_name_char(chars[0]) || read_name(&chars, 0) != name {
+        return false;
+    }
+    // constants with single letter names e.g "e"
+    if chars.len() == 1 && char_to_value(&chars[0]).is_some() {
+        return false;
+    }
+    return !FUNCTION_NAMES.contains(&name) && !OPERATOR_NAMES.contains(&name) && !VALUE_NAMES.contains(&name);
+}
+
+/// Converts a name into its value within the context. This is
+/// either a name from VALUE_NAMES, a variable, a constant or a unit.
+/// Values are given along with their dimension.
+/// 
+/// Returns None if no associated value is found.
+fn name_to_value<N: Numeric>(name: &str, ctx: &Context) -> Option<(N, Dimension)> {
+    if name == "Ans" {
+        return text_to_quantity(&ctx.ans, ctx);
+    }
+    if let Some(value) = ctx.variables.get(name) {
+        return text_to_quantity(value, ctx);
+    }
+    // constants with single letter names e.g "e"
+    let mut name_chars = name.chars();
+    if let (Some(ch), None) = (name_chars.next(), name_chars.next()) {
+        if let Some(value) = char_to_value(&ch).and_then(N::parse_literal) {
+            return Some((value, Dimension::NONE));
+        }
+    }
+    // units are worth their size in base units e.g km is 1000 m,
+    // and currencies their worth in the base currency
+    if let Some(unit) = units::find_unit(name) {
+        return Some((text_to_number(unit.scale)?, unit.dimension));
+    }
+    let scale = ctx.currencies.scale(name)?;
+    return Some((text_to_number(&scale)?, Dimension::MONEY));
+}
+
+/// Token for a value with the given dimension
+fn value_token<N: Numeric>(value: N, dimension: Dimension) -> Token<N> {
+    if dimension.is_none() {
+        return Token::Number(value);
+    }
+    return Token::Quantity(value, dimension);
+}
+
+/// Class for managing a stack of Operators.
+/// Each Operator is kept alongside the char offset it was read from.
 struct OperatorStack {
-    stack: Vec<Operator>,
-    highest_priority: i32
+    stack: Vec<(Operator, usize)>,
+    highest_priority: i32,
+    negation: NegationConvention
 }
 impl OperatorStack {
-    pub fn new() -> Self {
+    pub fn new(negation: NegationConvention) -> Self {
         return Self {
             stack: Vec::new(),
-            highest_priority: -1
+            highest_priority: -1,
+            negation
         };
     }
 
     /// Compute priority level of an Operator. (BEDMAS precedence)
-    fn get_priority(op: &Operator) -> i32 {
+    /// Negatives sit between multiplication and powers unless the
+    /// calculator convention is used, where they come before everything.
+    fn get_priority(&self, op: &Operator) -> i32 {
         match op {
             Operator::TwoInOperator(inside) => {
+                // bitwise operators come below arithmetic (as in C)
                 match inside {
-                    TwoInOperator::Add|TwoInOperator::Subtract => return 1,
-                    TwoInOperator::Multiply|TwoInOperator::Divide => return 2,
-                    TwoInOperator::Power => return 3,
+                    TwoInOperator::Or => return 1,
+                    TwoInOperator::Xor => return 2,
+                    TwoInOperator::And => return 3,
+                    TwoInOperator::ShiftLeft|TwoInOperator::ShiftRight => return 4,
+                    TwoInOperator::Add|TwoInOperator::Subtract => return 5,
+                    TwoInOperator::Multiply|TwoInOperator::Divide|TwoInOperator::Modulo => return 6,
+                    TwoInOperator::Power => return 8,
+                    TwoInOperator::Log => return 10
                 }
             }
-            Operator::OneInOperator(_) => return 4,
+            Operator::OneInOperator(OneInOperator::Negative) if self.negation == NegationConvention::Mathematical => return 7,
+            Operator::OneInOperator(_) | Operator::UserFunction(..) => return 10,
             _ => return 0
         }
     }
@@ -113,9 +741,12 @@ impl OperatorStack {
     /// 
     /// Pop details: element [0] of output would be the first 
     ///              Operator popped off when pushing
-    fn push(&mut self, op: Operator) -> Vec<Operator> {
-        let mut output: Vec<Operator> = Vec::new();
-        let curr_priority = Self::get_priority(&op);
+    /// 
+    /// Returns an UnbalancedBracket error if a close bracket has
+    /// no matching open bracket.
+    fn push(&mut self, op: Operator, offset: usize) -> Result<Vec<(Operator, usize)>, EvalError> {
+        let mut output: Vec<(Operator, usize)> = Vec::new();
+        let curr_priority = self.get_priority(&op);
 
         match &op {
             // Upon brackets
@@ -124,7 +755,7 @@ impl OperatorStack {
                     // For open bracket, reset precedence
                     Bracket::Open => {
                         self.highest_priority = 0;
-                        self.stack.push(op);
+                        self.stack.push((op, offset));
                     }
                     // For close bracket, pop operators off until
                     // open bracket is met
@@ -132,13 +763,14 @@ impl OperatorStack {
                         loop {
                             let stack_top = self.stack.pop();
                             match stack_top {
-                                None|Some(Operator::Bracket(Bracket::Open)) => break,
+                                None => return Err(EvalError::UnbalancedBracket(offset)),
+                                Some((Operator::Bracket(Bracket::Open), _)) => break,
                                 Some(_) => output.push(stack_top.unwrap())
                             }
                         }
                         // recalculate highest priority
                         if self.stack.last().is_some() {
-                            self.highest_priority = OperatorStack::get_priority(self.stack.last().unwrap());
+                            self.highest_priority = self.get_priority(&self.stack.last().unwrap().0);
                         } else {
                             self.highest_priority = -1;
                         }
@@ -146,16 +778,38 @@ impl OperatorStack {
                 }
             }
 
+            // Upon separators, pop operators off until the open
+            // bracket of the function is met (but leave the bracket)
+            Operator::Separator => {
+                loop {
+                    match self.stack.last() {
+                        None => return Err(EvalError::UnknownSymbol(offset)),
+                        Some((Operator::Bracket(Bracket::Open), _)) => break,
+                        Some(_) => output.push(self.stack.pop().unwrap())
+                    }
+                }
+                self.highest_priority = 0;
+            }
+
+            // Upon functions with two or more inputs, don't worry about precedence and
+            // push straight to stack (the same as single input operators)
+            Operator::TwoInOperator(TwoInOperator::Log) | Operator::UserFunction(..) => {
+                self.stack.push((op, offset));
+                self.highest_priority = curr_priority;
+            }
+
             // Upon two input operators, worry about precedence and pop off lower precedence operators
-            Operator::TwoInOperator(_) => {
-                // pop any higher/equal precedence operators off the stack
-                // and put them onto the output
-                while self.highest_priority >= curr_priority {
+            Operator::TwoInOperator(inside) => {
+                // pop any higher precedence operators off the stack and put them
+                // onto the output, along with equal precedence operators unless
+                // the operator is right associative
+                while self.highest_priority > curr_priority
+                    || (self.highest_priority == curr_priority && !inside.is_right_associative()) {
                     let stack_top = self.stack.pop();
                     output.push(stack_top.unwrap());
     
                     if self.stack.last().is_some() {          
-                        self.highest_priority = Self::get_priority(self.stack.last().unwrap());
+                        self.highest_priority = self.get_priority(&self.stack.last().unwrap().0);
                     } else {
                         self.highest_priority = -1;
                     }
@@ -163,136 +817,837 @@ impl OperatorStack {
     
                 // put new operator onto stack, update priority
                 self.highest_priority = curr_priority;
-                self.stack.push(op);
+                self.stack.push((op, offset));
             }
 
             // Upon single input operators, don't worry about precedence and push straight to stack
             Operator::OneInOperator(_) => {
-                self.stack.push(op);
+                self.stack.push((op, offset));
                 self.highest_priority = curr_priority;
             }
+
+            // Upon percents, which apply to what is before them, output straight away
+            Operator::Percent => output.push((op, offset)),
+
+            // Conversions never make it into Postfix
+            Operator::Convert => return Err(EvalError::UnknownSymbol(offset))
         }
-        return output;
+        return Ok(output);
     }
 }
 
 // Datatype to represent a postfix expression
-type Postfix = Vec<Token>;
+pub type Postfix<N> = Vec<PositionedToken<N>>;
 
-/// Converts an infix string expression to Postfix
-/// Infix expression assumed to contain no spaces.
-fn infix_to_postfix(expr: &String) -> Postfix {
-    let mut output: Postfix = Vec::new();
-    let mut operator_stack: OperatorStack = OperatorStack::new();
+/// Parses the contents of a numerics buffer into a Number Token.
+/// `offset` is the char offset of where the buffer started.
+fn parse_numerics<N: Numeric>(buffer: &str, offset: usize) -> Result<PositionedToken<N>, EvalError> {
+    match N::parse_literal(buffer) {
+        Some(num) => return Ok(PositionedToken{token: Token::Number(num), offset}),
+        None => return Err(EvalError::MalformedNumber(offset))
+    }
+}
+
+/// Base of the integer literal started by "0" and the given char
+/// (e.g 16 for "0x").
+/// 
+/// Returns None if the char isn't a base prefix.
+fn prefix_radix(ch: char) -> Option<u32> {
+    match ch {
+        'x' => return Some(16),
+        'o' => return Some(8),
+        'b' => return Some(2),
+        _ => return None
+    }
+}
+
+/// Multiply Token for a multiplication implied by writing two values
+/// next to each other, placed at the offset of the second value.
+fn implied_multiply<N: Numeric>(offset: usize) -> PositionedToken<N> {
+    return PositionedToken{token: Token::Operator(Operator::TwoInOperator(TwoInOperator::Multiply)), offset};
+}
+
+/// Reads a temperature written after a number (e.g "-40°F") on the
+/// scale of its unit, replacing the number (along with any negative
+/// before it) and the implied multiply at the end of the output Tokens.
+/// `scale` and `zero` are the size and zero of the unit.
+/// 
+/// Returns false if the unit doesn't directly follow a number.
+fn read_temperature<N: Numeric>(output: &mut Vec<PositionedToken<N>>, scale: &N, dimension: Dimension, zero: &str, offset: usize) -> Result<bool, EvalError> {
+    let mut start = match output.len().checked_sub(2) {
+        Some(start) => start,
+        None => return Ok(false)
+    };
+    let is_implied = output[start + 1].offset == offset
+        && matches!(output[start + 1].token, Token::Operator(Operator::TwoInOperator(TwoInOperator::Multiply)));
+    let mut num = match (&output[start].token, is_implied) {
+        (Token::Number(num), true) => num.clone(),
+        _ => return Ok(false)
+    };
+    if start > 0 && matches!(output[start - 1].token, Token::Operator(Operator::OneInOperator(OneInOperator::Negative))) {
+        num = num.negative().ok_or(EvalError::DomainError(offset))?;
+        start -= 1;
+    }
+    let zero = text_to_number::<N>(zero).ok_or(EvalError::MalformedNumber(offset))?;
+    let temperature = num.multiply(scale).and_then(|scaled| scaled.add(&zero))
+        .ok_or(EvalError::DomainError(offset))?;
+    let number_offset = output[start].offset;
+    output.truncate(start);
+    output.push(PositionedToken{token: Token::Quantity(temperature, dimension), offset: number_offset});
+    return Ok(true);
+}
+
+/// Reads an infix string expression into Tokens (kept in infix order).
+/// Spaces are skipped, but keep apart names written next to each other
+/// (e.g "5 km to mi").
+/// 
+/// Names are looked up in the context, and a "-" is read as a negative
+/// when it follows another operator. Brackets are not checked until the
+/// Tokens are converted to Postfix.
+/// 
+/// Units (e.g "km") are read as their size in base units, so "5km"
+/// is 5×1000 m. Temperatures written after a value (e.g "20°C") are
+/// read as a temperature on that scale, rather than a multiple of
+/// the unit's size. Units directly followed by a bracket (e.g "g(2)")
+/// give an UnknownSymbol error, as they read as a call to a function
+/// that isn't defined.
+/// 
+/// Multiplication is implied (and given its own Token) when a value ends
+/// right before a bracket, constant, variable or function starts e.g
+/// "2(3)", "2π", "(1)(2)" and "2sin(30)". Digits straight after a value
+/// (e.g "π2") give a MissingOperator error.
+pub fn parse_tokens<N: Numeric>(expr: &str, ctx: &Context) -> Result<Vec<PositionedToken<N>>, EvalError> {
+    let mut output: Vec<PositionedToken<N>> = Vec::new();
     let mut numerics_buffer: String = String::from("");
+    // char offset of where the numerics buffer started
+    let mut numerics_start: usize = 0;
     // track if previous token was an operator.
     // helps to distinguish if "-" means subtract or negative
     let mut prev_token_is_op = true;
 
-    // go through each char in postfix string
-    for ch in expr.chars() {
+    // go through each char in infix string
+    let chars: Vec<char> = expr.chars().collect();
+    let mut offset: usize = 0;
+    while offset < chars.len() {
+        let ch = chars[offset];
+        // integers written in another base (e.g 0xff) are read whole
+        if numerics_buffer == "0" {
+            if let Some(radix) = prefix_radix(ch) {
+                let digits: String = chars[offset + 1..].iter().take_while(|ch| ch.is_digit(radix)).collect();
+                if !digits.is_empty() {
+                    let value = u128::from_str_radix(&digits, radix).map_err(|_| EvalError::MalformedNumber(numerics_start))?;
+                    output.push(parse_numerics(&value.to_string(), numerics_start)?);
+                    numerics_buffer.clear();
+                    offset += 1 + digits.chars().count();
+                    continue;
+                }
+            }
+        }
         // if char is digit or decimal then append to numerics buffer
         if ch.is_digit(10) || ch == '.'{ 
+            if numerics_buffer.is_empty() {
+                if !prev_token_is_op {
+                    return Err(EvalError::MissingOperator(offset));
+                }
+                numerics_start = offset;
+            }
             numerics_buffer.push(ch);
             prev_token_is_op = false;
         }
         else { // char now must be operator or symbol constant
-            // convert numerics_buffer into f64 and place onto output
+            // convert numerics_buffer into a number and place onto output
             if !numerics_buffer.is_empty() {
-                output.push(Token::Number(numerics_buffer.parse::<f64>().unwrap()));
+                output.push(parse_numerics(&numerics_buffer, numerics_start)?);
                 numerics_buffer.clear();
             }
+            if ch.is_whitespace() {
+                offset += 1;
+                continue;
+            }
+
+            // check for operators written as names e.g "mod"
+            if let Some(name) = match_name(&chars, offset, &OPERATOR_NAMES) {
+                output.push(PositionedToken{token: Token::Operator(name_to_operator(name).unwrap()), offset});
+                prev_token_is_op = true;
+                offset += name.chars().count();
+                continue;
+            }
+
+            // check for function names that span multiple chars
+            if let Some(name) = match_name(&chars, offset, &FUNCTION_NAMES) {
+                if !prev_token_is_op {
+                    output.push(implied_multiply(offset));
+                }
+                output.push(PositionedToken{token: Token::Operator(name_to_operator(name).unwrap()), offset});
+                prev_token_is_op = true;
+                offset += name.chars().count();
+                continue;
+            }
 
-            let mut potential_op = char_to_operator(&ch);
-            if potential_op.is_some() { // check if char is a valid operator char
-                
+            // check for names of user defined functions, values, variables
+            // and units (including temperature units e.g "°C")
+            if is_name_char(ch) || ch == '°' {
+                let name = match ch {
+                    '°' => format!("°{}", read_name(&chars, offset + 1)),
+                    _ => read_name(&chars, offset)
+                };
+                let name_len = name.chars().count();
+                if !prev_token_is_op {
+                    output.push(implied_multiply(offset));
+                }
+                if let Some(function) = ctx.functions.get(&name) {
+                    // calls to user defined functions must have brackets
+                    if chars.get(offset + name_len) != Some(&'(') {
+                        return Err(EvalError::ArgumentCount(offset));
+                    }
+                    let arity = function.parameters.len();
+                    output.push(PositionedToken{token: Token::Operator(Operator::UserFunction(name, arity)), offset});
+                    prev_token_is_op = true;
+                    offset += name_len;
+                    continue;
+                }
+                let (value, dimension) = name_to_value::<N>(&name, ctx).ok_or(EvalError::UnknownSymbol(offset))?;
+                let is_unit = !ctx.variables.contains_key(&name)
+                    && (units::find_unit(&name).is_some() || ctx.currencies.scale(&name).is_some());
+                if is_unit && chars.get(offset + name_len) == Some(&'(') {
+                    return Err(EvalError::UnknownSymbol(offset));
+                }
+                // a temperature after a number is taken on the unit's scale
+                let temperature_offset = units::find_unit(&name).and_then(|unit| unit.offset)
+                    .filter(|_| is_unit);
+                if let Some(zero) = temperature_offset {
+                    if read_temperature(&mut output, &value, dimension, zero, offset)? {
+                        prev_token_is_op = false;
+                        offset += name_len;
+                        continue;
+                    }
+                }
+                output.push(PositionedToken{token: value_token(value, dimension), offset});
+                prev_token_is_op = false;
+                offset += name_len;
+                continue;
+            }
+
+            if let Some(mut op) = char_to_operator(&ch) { // check if char is a valid operator char
                 // upon a subtract Operator, decide if it should be interpreted
                 // as a negative Operator
-                if prev_token_is_op && matches!(potential_op, Some(Operator::TwoInOperator(TwoInOperator::Subtract))) {
-                    potential_op = Some(Operator::OneInOperator(OneInOperator::Negative));
+                if prev_token_is_op && op == Operator::TwoInOperator(TwoInOperator::Subtract) {
+                    op = Operator::OneInOperator(OneInOperator::Negative);
                 }
-                
-                // place associated Operator enum onto operator stack
-                let pop_offs: Vec<Operator> = operator_stack.push(potential_op.unwrap());
-                if !pop_offs.is_empty() {
-                    // append any Operators popped off onto RPN output
-                    for op in pop_offs {
-                        output.push(Token::Operator(op));
-                    }
+                // brackets, square roots and NOTs start a value, so
+                // multiply them onto any value before
+                if !prev_token_is_op && matches!(op, Operator::Bracket(Bracket::Open) | Operator::OneInOperator(OneInOperator::SquareRoot | OneInOperator::Not)) {
+                    output.push(implied_multiply(offset));
                 }
-                prev_token_is_op = true;
+                // factorials and close brackets end a value, so a "-"
+                // after them is a subtraction
+                prev_token_is_op = !matches!(op, Operator::OneInOperator(OneInOperator::Factorial) | Operator::Percent | Operator::Bracket(Bracket::Close));
+                output.push(PositionedToken{token: Token::Operator(op), offset});
             } else { // char now must be a symbol constant
-                output.push(Token::Number(char_to_value(&ch).unwrap()));
+                if !prev_token_is_op {
+                    output.push(implied_multiply(offset));
+                }
+                match char_to_value(&ch).and_then(N::parse_literal) {
+                    Some(value) => output.push(PositionedToken{token: Token::Number(value), offset}),
+                    None => return Err(EvalError::UnknownSymbol(offset))
+                }
                 prev_token_is_op = false;
             }
         }
+        offset += 1;
     }
 
     // convert any remaining numeric buffer and dump onto output
     if !numerics_buffer.is_empty() {
-        output.push(Token::Number(numerics_buffer.parse::<f64>().unwrap()));
+        output.push(parse_numerics(&numerics_buffer, numerics_start)?);
     }
-    // append remaining operators in operator_stack onto output
-    for op in operator_stack.stack.iter().rev() {
-        output.push(Token::Operator(op.clone()));
+    return Ok(output);
+}
+
+/// Converts Tokens in infix order (as given by parse_tokens) to Postfix.
+/// Also checks brackets are balanced and functions are given the right
+/// number of inputs.
+pub fn tokens_to_postfix<N: Numeric>(tokens: Vec<PositionedToken<N>>, ctx: &Context) -> Result<Postfix<N>, EvalError> {
+    let mut output: Postfix<N> = Vec::new();
+    let mut operator_stack: OperatorStack = OperatorStack::new(ctx.negation);
+    // function whose open bracket could come next
+    let mut pending_call: Option<FunctionCall> = None;
+    // for each open bracket, the function call it belongs to (if any).
+    // used to check functions are given the right number of inputs
+    let mut bracket_calls: Vec<Option<FunctionCall>> = Vec::new();
+
+    for PositionedToken{token, offset} in tokens {
+        // a function only takes the open bracket directly after it
+        let waiting_call: Option<FunctionCall> = pending_call.take();
+        let op = match token {
+            // numbers go straight onto the output
+            Token::Number(_) | Token::Quantity(..) => {
+                output.push(PositionedToken{token, offset});
+                continue;
+            }
+            Token::Operator(op) => op
+        };
+
+        match &op {
+            // factorials and percents come after what they apply to,
+            // so can go straight onto the RPN output
+            Operator::OneInOperator(OneInOperator::Factorial) | Operator::Percent => {
+                output.push(PositionedToken{token: Token::Operator(op), offset});
+                continue;
+            }
+            // conversions apply to a whole expression, so are
+            // taken out before it is converted to Postfix
+            Operator::Convert => return Err(EvalError::UnknownSymbol(offset)),
+            // keep count of the inputs given to functions
+            Operator::Bracket(Bracket::Open) => bracket_calls.push(waiting_call),
+            Operator::Separator => {
+                match bracket_calls.last_mut() {
+                    Some(Some(call)) => call.inputs += 1,
+                    // separators only belong within function calls
+                    _ => return Err(EvalError::UnknownSymbol(offset))
+                }
+            }
+            Operator::Bracket(Bracket::Close) => {
+                if let Some(Some(call)) = bracket_calls.pop() {
+                    if call.inputs != call.arity {
+                        return Err(EvalError::ArgumentCount(call.offset));
+                    }
+                }
+            }
+            _ => {
+                pending_call = op.function_arity().map(|arity| FunctionCall{arity, offset, inputs: 1});
+            }
+        }
+
+        // place Operator onto operator stack
+        let pop_offs: Vec<(Operator, usize)> = operator_stack.push(op, offset)?;
+        // append any Operators popped off onto RPN output
+        for (op, op_offset) in pop_offs {
+            output.push(PositionedToken{token: Token::Operator(op), offset: op_offset});
+        }
     }
-    return output;
+
+    // append remaining operators in operator_stack onto output,
+    // any open brackets left over were never closed
+    for (op, op_offset) in operator_stack.stack.iter().rev() {
+        if matches!(op, Operator::Bracket(_)) {
+            return Err(EvalError::UnbalancedBracket(*op_offset));
+        }
+        output.push(PositionedToken{token: Token::Operator(op.clone()), offset: *op_offset});
+    }
+    return Ok(output);
+}
+
+/// Converts an infix string expression to Postfix
+pub fn infix_to_postfix<N: Numeric>(expr: &str, ctx: &Context) -> Result<Postfix<N>, EvalError> {
+    return tokens_to_postfix(parse_tokens(expr, ctx)?, ctx);
+}
+
+/// Reads a Postfix expression and evaluates the final answer
+/// (in base units if it has any).
+pub fn evaluate_postfix<N: Numeric>(expr: &Postfix<N>, ctx: &Context) -> Result<N, EvalError> {
+    return run_postfix(expr, ctx, |_, _| ()).map(|answer| answer.value);
+}
+
+/// The working stack after a Token of Postfix was read
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalStep<N: Numeric> {
+    pub token: PositionedToken<N>,
+    pub stack: Vec<N>
 }
 
-/// Reads a Postfix expression and evaluates the final answer.
-fn evaluate_postfix(expr: &Postfix) -> Option<f64> {
-    let mut working_stack: Vec<f64> = Vec::new();
-    for token in expr.iter() {
+/// Evaluates a Postfix expression, recording the working stack after
+/// each Token is read. Steps up to an error are still given.
+pub fn trace_postfix<N: Numeric>(expr: &Postfix<N>, ctx: &Context) -> (Vec<EvalStep<N>>, Result<N, EvalError>) {
+    let mut steps: Vec<EvalStep<N>> = Vec::new();
+    let result = run_postfix(expr, ctx, |token, stack| {
+        steps.push(EvalStep{token: token.clone(), stack: stack.to_vec()});
+    });
+    return (steps, result.map(|answer| answer.value));
+}
+
+/// Evaluates a Postfix expression, calling `on_step` with each Token
+/// and the working stack after it was read.
+fn run_postfix<N: Numeric, F: FnMut(&PositionedToken<N>, &[N])>(expr: &Postfix<N>, ctx: &Context, mut on_step: F) -> Result<Quantity<N>, EvalError> {
+    let mut working_stack: Vec<N> = Vec::new();
+    // whether each number on working_stack is a percentage, which changes
+    // how it is added to or subtracted from another number
+    let mut is_percent: Vec<bool> = Vec::new();
+    // dimension of each number on working_stack
+    let mut dimensions: Vec<Dimension> = Vec::new();
+    // offset where the expression giving each number on working_stack starts
+    let mut starts: Vec<usize> = Vec::new();
+    for positioned in expr.iter() {
+        let PositionedToken{token, offset} = positioned;
         match token {
             // upon a number, push it to working_stack
             Token::Number(num) => {
-                working_stack.push(*num);
+                working_stack.push(num.clone());
+                is_percent.push(false);
+                dimensions.push(Dimension::NONE);
+                starts.push(*offset);
+            }
+            Token::Quantity(num, dimension) => {
+                working_stack.push(num.clone());
+                is_percent.push(false);
+                dimensions.push(*dimension);
+                starts.push(*offset);
             }
             // upon an operator, apply it to working_stack
             Token::Operator(op) => {
                 // grab top of working_stack
-                let mut pop_off = working_stack.pop();
-                if pop_off.is_none() {
-                    return None;
-                }
-                let right_number = pop_off.unwrap();
+                let right_number = working_stack.pop().ok_or(EvalError::MissingOperand(*offset))?;
+                let right_is_percent = is_percent.pop().unwrap_or(false);
+                let right_dimension = dimensions.pop().unwrap_or(Dimension::NONE);
+                // prefix operators (e.g "-" or "sin") start before their input
+                let mut start = starts.pop().unwrap_or(*offset).min(*offset);
         
                 match op { 
                     // check 1 input operators
-                    Operator::OneInOperator(inside) => working_stack.push(inside.apply(right_number)),
+                    Operator::OneInOperator(inside) => {
+                        let dimension = one_in_dimension(inside, right_dimension).ok_or(EvalError::DimensionMismatch(*offset))?;
+                        let result = inside.apply(right_number, ctx.angle_mode).ok_or(EvalError::DomainError(*offset))?;
+                        working_stack.push(result);
+                        // a negative percentage is still a percentage e.g 200+-10%
+                        is_percent.push(right_is_percent && *inside == OneInOperator::Negative);
+                        dimensions.push(dimension);
+                        starts.push(start);
+                    },
 
                     // check 2 input operators
                     Operator::TwoInOperator(inside) => {
                         // grab top of working_stack again
-                        pop_off = working_stack.pop();
-                        if pop_off.is_none() {
-                            return None;
+                        let left_number = working_stack.pop().ok_or(EvalError::MissingOperand(*offset))?;
+                        is_percent.pop();
+                        let left_dimension = dimensions.pop().unwrap_or(Dimension::NONE);
+                        start = starts.pop().unwrap_or(start).min(start);
+                        // adding a percentage adds that percentage of the left input
+                        let (right_number, right_dimension) = match inside {
+                            TwoInOperator::Add | TwoInOperator::Subtract if right_is_percent => {
+                                let number = left_number.multiply(&right_number).ok_or(EvalError::DomainError(*offset))?;
+                                (number, left_dimension.multiply(&right_dimension))
+                            }
+                            _ => (right_number, right_dimension)
+                        };
+                        let dimension = two_in_dimension(inside, left_dimension, right_dimension, &right_number)
+                            .ok_or(EvalError::DimensionMismatch(*offset))?;
+                        let result = inside.apply(left_number, right_number).ok_or(EvalError::DomainError(*offset))?;
+                        working_stack.push(result);
+                        is_percent.push(false);
+                        dimensions.push(dimension);
+                        starts.push(start);
+                    },
+                    Operator::Percent => {
+                        let hundred = N::parse_literal("100").ok_or(EvalError::MalformedNumber(*offset))?;
+                        let result = right_number.divide(&hundred).ok_or(EvalError::DomainError(*offset))?;
+                        working_stack.push(result);
+                        is_percent.push(true);
+                        dimensions.push(right_dimension);
+                        starts.push(start);
+                    },
+                    // check user defined functions
+                    Operator::UserFunction(name, arity) => {
+                        // grab the rest of the inputs from working_stack
+                        let mut inputs: Vec<Quantity<N>> = vec![Quantity{value: right_number, dimension: right_dimension, unit: None}];
+                        for _ in 1..*arity {
+                            let value = working_stack.pop().ok_or(EvalError::MissingOperand(*offset))?;
+                            let dimension = dimensions.pop().unwrap_or(Dimension::NONE);
+                            inputs.push(Quantity{value, dimension, unit: None});
+                            start = starts.pop().unwrap_or(start).min(start);
                         }
-                        let left_number = pop_off.unwrap();
-                        working_stack.push(inside.apply(left_number, right_number));
+                        inputs.reverse();
+                        is_percent.truncate(working_stack.len());
+                        let answer = call_user_function(name, inputs, ctx, *offset)?;
+                        working_stack.push(answer.value);
+                        is_percent.push(false);
+                        dimensions.push(answer.dimension);
+                        starts.push(start);
                     },
-                    // this should be impossible to trigger?
-                    _ => return None
+                    // brackets never make it into the postfix output
+                    Operator::Bracket(_) => return Err(EvalError::UnbalancedBracket(*offset)),
+                    Operator::Separator | Operator::Convert => return Err(EvalError::UnknownSymbol(*offset))
                 }
             }
         }
+        on_step(positioned, &working_stack);
     }
         
-    // multiple numbers could still be left on the stack due
-    // to postfix expressions having implied multiplication
-    // e.g ab = a*b
-    while working_stack.len() > 1 {
-        let right_number: f64 = working_stack.pop().unwrap();
-        let left_number: f64 = working_stack.pop().unwrap();
-        working_stack.push(left_number*right_number);
+    // numbers left over on the stack had no operator between them
+    // (implied multiplications are added when parsing, so only
+    // Postfix built by hand can have any)
+    if working_stack.len() > 1 {
+        return Err(EvalError::MissingOperator(starts[1]));
+    }
+    // return answer, an empty expression has nothing to answer with
+    let value = working_stack.pop().ok_or(EvalError::MissingOperand(0))?;
+    return Ok(Quantity{value, dimension: dimensions.pop().unwrap_or(Dimension::NONE), unit: None});
+}
+
+/// Dimension of the answer of a single input operator.
+/// Functions such as sin and ln only take plain numbers.
+/// 
+/// Returns None if the operator can't take the input's dimension.
+pub(crate) fn one_in_dimension(op: &OneInOperator, dimension: Dimension) -> Option<Dimension> {
+    if dimension.is_none() {
+        return Some(dimension);
+    }
+    match op {
+        OneInOperator::Negative | OneInOperator::Abs | OneInOperator::Floor
+        | OneInOperator::Ceil | OneInOperator::Round => return Some(dimension),
+        OneInOperator::SquareRoot => return dimension.root(2),
+        _ => return None
+    }
+}
+
+/// Dimension of the answer of a two input operator. Only quantities of
+/// the same dimension can be added or subtracted, and only raised to
+/// whole powers.
+/// 
+/// Returns None if the operator can't take the inputs' dimensions.
+pub(crate) fn two_in_dimension<N: Numeric>(op: &TwoInOperator, left: Dimension, right: Dimension, right_number: &N) -> Option<Dimension> {
+    match op {
+        TwoInOperator::Add | TwoInOperator::Subtract | TwoInOperator::Modulo => {
+            if left != right {
+                return None;
+            }
+            return Some(left);
+        }
+        TwoInOperator::Multiply => return Some(left.multiply(&right)),
+        TwoInOperator::Divide => return Some(left.divide(&right)),
+        TwoInOperator::Power => {
+            if !right.is_none() {
+                return None;
+            }
+            if left.is_none() {
+                return Some(left);
+            }
+            let exponent = right_number.to_f64()?;
+            if exponent.fract() != 0.0 {
+                return None;
+            }
+            return Some(left.power(exponent as i32));
+        }
+        _ => {
+            if !left.is_none() || !right.is_none() {
+                return None;
+            }
+            return Some(left);
+        }
+    }
+}
+
+/// Evaluates a call to a user defined function with the given inputs.
+/// `offset` is where the call is, which errors from within the function
+/// are reported at.
+pub(crate) fn call_user_function<N: Numeric>(name: &str, inputs: Vec<Quantity<N>>, ctx: &Context, offset: usize) -> Result<Quantity<N>, EvalError> {
+    let function = ctx.functions.get(name).ok_or(EvalError::UnknownSymbol(offset))?;
+    if ctx.call_depth >= MAX_CALL_DEPTH {
+        return Err(EvalError::RecursionLimit(offset));
+    }
+    // evaluate the function's body with its parameters set as variables
+    let mut body_ctx = ctx.clone();
+    body_ctx.call_depth += 1;
+    for (parameter, input) in function.parameters.iter().zip(inputs) {
+        body_ctx.variables.insert(parameter.clone(), quantity_text(input, ctx));
+    }
+    let answer = evaluate_quantity::<N>(&function.body, &body_ctx).map_err(|error| error.moved_to(offset))?;
+    // converted answers are taken back into base units
+    if answer.unit.is_some() {
+        let (value, dimension) = text_to_quantity(&answer.to_string(), ctx).ok_or(EvalError::DomainError(offset))?;
+        return Ok(Quantity{value, dimension, unit: None});
+    }
+    return Ok(answer);
+}
+
+/// Converts an ASCII operator char (e.g '*' or '<') into the
+/// calculator's own, leaving other chars as they are.
+pub fn convert_plain_char(ch: char) -> char {
+    match ch {
+        '*' => return '×',
+        '/' => return '÷',
+        '~' => return '¬',
+        '<' => return '«',
+        '>' => return '»',
+        _ => return ch
     }
-    // return answer
-    return working_stack.last().copied();
+}
+
+/// Whether a converted char repeats the shift just before it. Shifts
+/// can be written with one or two chars (e.g "<" or "<<"), so a
+/// repeated shift is dropped.
+pub fn is_repeated_shift(before: Option<char>, ch: char) -> bool {
+    return matches!(ch, '«' | '»') && before == Some(ch);
+}
+
+/// Converts plain text (e.g typed into a terminal or pasted) into an
+/// expression. ASCII operators (e.g "*" or "<<") are mapped onto the
+/// calculator's own and whitespace is removed, besides single spaces
+/// keeping apart names (e.g "5 km to mi") or numbers (so "2 3" is
+/// a missing operator rather than 23).
+pub fn convert_plain_text(text: &str) -> String {
+    return convert_plain_text_with_offsets(text).0;
+}
+
+/// Converts plain text as convert_plain_text does, along with the
+/// offset within the plain text of each converted char (and lastly of
+/// the end of the text), so errors can be reported where they were typed.
+pub fn convert_plain_text_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut converted = String::new();
+    let mut offsets: Vec<usize> = Vec::new();
+    for (i, ch) in chars.iter().enumerate() {
+        let before = converted.chars().last();
+        if ch.is_whitespace() {
+            let after = chars[i + 1..].iter().find(|ch| !ch.is_whitespace());
+            let is_numeric = |ch: char| ch.is_ascii_digit() || ch == '.';
+            let keeps_apart = match (before, after) {
+                (Some(before), Some(after)) if is_name_char(*after) => is_name_char(before) || before.is_ascii_digit(),
+                (Some(before), Some(after)) => is_numeric(before) && is_numeric(*after),
+                _ => false
+            };
+            if keeps_apart {
+                converted.push(' ');
+                offsets.push(i);
+            }
+            continue;
+        }
+        let ch = convert_plain_char(*ch);
+        if !is_repeated_shift(before, ch) {
+            converted.push(ch);
+            offsets.push(i);
+        }
+    }
+    offsets.push(chars.len());
+    return (converted, offsets);
 }
 
 // Evaluates the answer to an infix string expression
-pub fn evaluate_infix_expr(expr: &String) -> Option<f64> {
-    return evaluate_postfix(&infix_to_postfix(expr));
+// (in base units, or the units converted to)
+pub fn evaluate_infix_expr<N: Numeric>(expr: &str, ctx: &Context) -> Result<N, EvalError> {
+    return evaluate_quantity(expr, ctx).map(|answer| answer.value);
+}
+
+/// Evaluates the answer to an infix string expression along with its
+/// dimension. An expression can end in a conversion to other units
+/// (e.g "5 km to mi"), giving the answer in those units. Only units of
+/// the same dimension can be converted between.
+pub fn evaluate_quantity<N: Numeric>(expr: &str, ctx: &Context) -> Result<Quantity<N>, EvalError> {
+    let mut tokens: Vec<PositionedToken<N>> = parse_tokens(expr, ctx)?;
+    let (target_tokens, convert_offset) = match split_conversion(&mut tokens) {
+        Some(conversion) => conversion,
+        None => return run_postfix(&tokens_to_postfix(tokens, ctx)?, ctx, |_, _| ())
+    };
+    let answer = run_postfix(&tokens_to_postfix(tokens, ctx)?, ctx, |_, _| ())?;
+    // the units to convert to, as written after "to" or "in"
+    let target_text: String = expr.chars().skip(convert_offset + 2).collect();
+    let target_text = String::from(target_text.trim());
+    if target_tokens.is_empty() {
+        return Err(EvalError::MissingOperand(convert_offset));
+    }
+    let target = run_postfix(&tokens_to_postfix(target_tokens, ctx)?, ctx, |_, _| ())?;
+    return convert_quantity(answer, target, target_text, convert_offset);
+}
+
+/// Splits off the Tokens after a conversion (e.g "to mi" in "5 km to mi"),
+/// which can't be within brackets. The conversion itself is removed.
+/// 
+/// Returns the Tokens after the conversion and its offset,
+/// or None if there is no conversion.
+fn split_conversion<N: Numeric>(tokens: &mut Vec<PositionedToken<N>>) -> Option<(Vec<PositionedToken<N>>, usize)> {
+    let mut depth: i32 = 0;
+    let convert_at = tokens.iter().position(|token| {
+        match token.token {
+            Token::Operator(Operator::Bracket(Bracket::Open)) => depth += 1,
+            Token::Operator(Operator::Bracket(Bracket::Close)) => depth -= 1,
+            _ => ()
+        }
+        return depth == 0 && matches!(token.token, Token::Operator(Operator::Convert));
+    })?;
+    let target_tokens = tokens.split_off(convert_at + 1);
+    let convert_offset = tokens.pop().unwrap().offset;
+    return Some((target_tokens, convert_offset));
+}
+
+/// Checks an infix string expression can be read (including any
+/// conversion at its end), without evaluating it.
+fn check_expression<N: Numeric>(expr: &str, ctx: &Context) -> Result<(), EvalError> {
+    let mut tokens: Vec<PositionedToken<N>> = parse_tokens(expr, ctx)?;
+    let conversion = split_conversion(&mut tokens);
+    tokens_to_postfix(tokens, ctx)?;
+    if let Some((target_tokens, convert_offset)) = conversion {
+        if target_tokens.is_empty() {
+            return Err(EvalError::MissingOperand(convert_offset));
+        }
+        tokens_to_postfix(target_tokens, ctx)?;
+    }
+    return Ok(());
+}
+
+/// Converts a quantity into the units of `target`, written as
+/// `target_text`. `offset` is where the conversion is, which
+/// errors are reported at.
+pub(crate) fn convert_quantity<N: Numeric>(answer: Quantity<N>, target: Quantity<N>, target_text: String, offset: usize) -> Result<Quantity<N>, EvalError> {
+    // plain numbers (e.g "3 to 4") have no units to convert to
+    if answer.dimension != target.dimension || target.dimension.is_none() {
+        return Err(EvalError::DimensionMismatch(offset));
+    }
+    // temperatures are converted onto the unit's scale e.g 293.15 K to °C is 20
+    let mut value = answer.value;
+    if let Some(zero) = units::find_unit(&target_text).and_then(|unit| unit.offset) {
+        let zero = text_to_number::<N>(zero).ok_or(EvalError::MalformedNumber(offset))?;
+        value = value.subtract(&zero).ok_or(EvalError::DomainError(offset))?;
+    }
+    let value = value.divide(&target.value).ok_or(EvalError::DomainError(offset))?;
+    return Ok(Quantity{value, dimension: answer.dimension, unit: Some(target_text)});
+}
+
+/// Evaluates an infix string expression using the number type
+/// of the given mode. The answer is given in text form.
+pub fn evaluate_with_mode(expr: &str, mode: NumberMode, ctx: &Context) -> Result<String, EvalError> {
+    match mode {
+        NumberMode::Float => return evaluate_to_text::<f64>(expr, ctx),
+        NumberMode::Decimal => return evaluate_to_text::<Decimal>(expr, ctx),
+        NumberMode::Rational => return evaluate_to_text::<Rational>(expr, ctx),
+        NumberMode::Complex => return evaluate_to_text::<Complex>(expr, ctx),
+        NumberMode::Programmer => return with_word_type!(ctx.word_size, evaluate_to_text(expr, ctx))
+    }
+}
+
+/// Evaluates an infix string expression, giving the answer in text form.
+fn evaluate_to_text<N: Numeric>(expr: &str, ctx: &Context) -> Result<String, EvalError> {
+    let answer = evaluate_quantity::<N>(expr, ctx)?;
+    return Ok(quantity_text(answer, ctx));
+}
+
+/// Text of a quantity which can be read back as an expression,
+/// with amounts of money labelled in the base currency.
+fn quantity_text<N: Numeric>(mut quantity: Quantity<N>, ctx: &Context) -> String {
+    if let (None, true, Some(base)) = (&quantity.unit, quantity.dimension.has_money(), &ctx.currencies.base) {
+        quantity.unit = Some(quantity.dimension.to_string().replace('¤', base));
+    }
+    return quantity.to_string();
+}
+
+/// The working of an evaluation in text form, for showing to the user
+#[derive(Clone, Debug, PartialEq)]
+pub struct Working {
+    // Expression that was evaluated
+    pub expression: String,
+    // Each Token of the expression's Postfix
+    pub postfix: Vec<String>,
+    // Each Token read and the working stack after it (top of stack last)
+    pub steps: Vec<(String, Vec<String>)>,
+    // Final answer or the error met
+    pub result: Result<String, EvalError>
+}
+
+/// Gives the working of an expression's evaluation.
+/// The working shown is of the part before any conversion
+/// (e.g "5 km" of "5 km to mi"), which is converted at the end.
+fn working_of<N: Numeric>(expr: &str, ctx: &Context) -> Result<Working, EvalError> {
+    let mut tokens: Vec<PositionedToken<N>> = parse_tokens(expr, ctx)?;
+    let conversion = split_conversion(&mut tokens);
+    let postfix: Postfix<N> = tokens_to_postfix(tokens, ctx)?;
+    let (steps, result) = trace_postfix(&postfix, ctx);
+    let result = match conversion {
+        Some(_) => evaluate_to_text::<N>(expr, ctx),
+        None => result.map(|answer| answer.to_string())
+    };
+    return Ok(Working {
+        expression: String::from(expr),
+        postfix: postfix.iter().map(|token| token.token.to_string()).collect(),
+        steps: steps.iter()
+            .map(|step| (step.token.token.to_string(), step.stack.iter().map(|num| num.to_string()).collect()))
+            .collect(),
+        result
+    });
+}
+
+/// Gives the working of an expression's evaluation using the number
+/// type of the given mode.
+/// 
+/// Returns an error if the expression could not be converted to Postfix.
+pub fn working_with_mode(expr: &str, mode: NumberMode, ctx: &Context) -> Result<Working, EvalError> {
+    match mode {
+        NumberMode::Float => return working_of::<f64>(expr, ctx),
+        NumberMode::Decimal => return working_of::<Decimal>(expr, ctx),
+        NumberMode::Rational => return working_of::<Rational>(expr, ctx),
+        NumberMode::Complex => return working_of::<Complex>(expr, ctx),
+        NumberMode::Programmer => return with_word_type!(ctx.word_size, working_of(expr, ctx))
+    }
+}
+
+/// The result of evaluating a statement
+#[derive(Debug, PartialEq)]
+pub enum StatementResult {
+    // Answer to an expression or variable assignment (in text form)
+    Answer(String),
+    // Name of a function that was defined
+    Defined(String)
+}
+
+/// Reads the head of a function definition e.g "f(x,y)" into
+/// the function's name and parameters.
+/// 
+/// Returns None if the head is not valid.
+fn parse_function_head(head: &str) -> Option<(String, Vec<String>)> {
+    let (name, rest) = head.split_once('(')?;
+    let parameters: Vec<String> = rest.strip_suffix(')')?.split(',').map(String::from).collect();
+    if !is_valid_variable_name(name) || !parameters.iter().all(|parameter| is_valid_variable_name(parameter)) {
+        return None;
+    }
+    // parameters can't share names
+    for (i, parameter) in parameters.iter().enumerate() {
+        if parameters[..i].contains(parameter) {
+            return None;
+        }
+    }
+    return Some((String::from(name), parameters));
+}
+
+/// Evaluates a statement using the number type of the given mode.
+/// A statement is either:
+///  - an expression
+///  - an assignment of an expression to a variable (e.g "rate=0.07")
+///  - a function definition (e.g "f(x,y)=x^2+y")
+/// 
+/// Assigned variables and defined functions are stored into the context.
+pub fn evaluate_statement(statement: &str, mode: NumberMode, ctx: &mut Context) -> Result<StatementResult, EvalError> {
+    match statement.split_once('=') {
+        None => return evaluate_with_mode(statement, mode, ctx).map(StatementResult::Answer),
+        Some((head, body)) => {
+            // offset of the body within the statement
+            let body_offset = head.chars().count() + 1;
+            let body = String::from(body);
+
+            // function definitions
+            if head.contains('(') {
+                let (name, parameters) = parse_function_head(head).ok_or(EvalError::InvalidAssignment(0))?;
+                let function = UserFunction{parameters, body};
+                // check the body can be read, with parameters given placeholder values
+                let mut check_ctx = ctx.clone();
+                check_ctx.functions.insert(name.clone(), function.clone());
+                for parameter in function.parameters.iter() {
+                    check_ctx.variables.insert(parameter.clone(), String::from("1"));
+                }
+                check_expression::<f64>(&function.body, &check_ctx).map_err(|error| error.shift(body_offset))?;
+
+                ctx.variables.remove(&name);
+                ctx.functions.insert(name.clone(), function);
+                return Ok(StatementResult::Defined(name));
+            }
+
+            // variable assignments
+            if !is_valid_variable_name(head) {
+                return Err(EvalError::InvalidAssignment(0));
+            }
+            let answer = evaluate_with_mode(&body, mode, ctx)
+                .map_err(|error| error.shift(body_offset))?;
+            ctx.functions.remove(head);
+            ctx.variables.insert(String::from(head), answer.clone());
+            return Ok(StatementResult::Answer(answer));
+        }
+    }
 }
 
 
@@ -303,6 +1658,7 @@ mod tests {
     //       including output from tests (not sure about other OSs).
     //       Comment out  #![..  to read output if needed.
     use super::*;
+    use crate::currency::RatesError;
 
     #[test]
     fn basic_expressions() {
@@ -315,7 +1671,7 @@ mod tests {
             ("4^3.5", 128.0)
         ];
         for item in expr_and_ans {
-            assert_eq!(evaluate_infix_expr(&String::from(item.0)).unwrap(), item.1);
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &Context::new()).unwrap(), item.1);
         }
     }
     #[test]
@@ -324,7 +1680,7 @@ mod tests {
             ("3.6+(23.2-6×3^2÷3+5)×1.5", 18.9)
         ];
         for item in expr_and_ans {
-            assert_eq!(evaluate_infix_expr(&String::from(item.0)).unwrap(), item.1);
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &Context::new()).unwrap(), item.1);
         }
     }
     #[test]
@@ -336,16 +1692,565 @@ mod tests {
             ("-20+6×-(5÷2)", -35.0)
         ];
         for item in expr_and_ans {
-            assert_eq!(evaluate_infix_expr(&String::from(item.0)).unwrap(), item.1);
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &Context::new()).unwrap(), item.1);
         }
     }
     #[test]
     fn one_input_operator_chaining() {
         // a chain of one input operators should be applied in the reverse at which
         // they appear LTR (i.e the very inside is applied first)
-        assert_eq!(evaluate_infix_expr(&String::from("-√25")).unwrap(), -5.0);
-        // complex number should result in f64::NAN
-        assert!(evaluate_infix_expr(&String::from("√-36")).unwrap().is_nan())
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("-√25"), &Context::new()).unwrap(), -5.0);
+        // complex number should result in f64::NAN, unless using complex numbers
+        assert!(evaluate_infix_expr::<f64>(&String::from("√-36"), &Context::new()).unwrap().is_nan());
+        assert_eq!(evaluate_with_mode(&String::from("√-36"), NumberMode::Complex, &Context::new()).unwrap(), "6i");
     }
-}
+    #[test]
+    fn evaluation_errors() {
+        let expr_and_err = [
+            ("(3+4", EvalError::UnbalancedBracket(0)),
+            ("3+4)", EvalError::UnbalancedBracket(3)),
+            ("3×", EvalError::MissingOperand(1)),
+            ("", EvalError::MissingOperand(0)),
+            ("1.2.3+4", EvalError::MalformedNumber(0)),
+            ("2+5$", EvalError::UnknownSymbol(3)),
+            ("6÷(2-2)", EvalError::DomainError(1))
+        ];
+        for item in expr_and_err {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &Context::new()).unwrap_err(), item.1);
+        }
+    }
+    #[test]
+    fn decimal_expressions() {
+        let expr_and_ans = [
+            ("0.1+0.2", "0.3"),
+            ("19.99×3", "59.97"),
+            ("100.10-0.1", "100"),
+            ("1÷8", "0.125"),
+            ("2^-2", "0.25"),
+            ("√2.25", "1.5"),
+            ("3.6+(23.2-6×3^2÷3+5)×1.5", "18.9")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(&String::from(item.0), NumberMode::Decimal, &Context::new()).unwrap(), item.1);
+        }
+        // decimals have no NaN to fall back on
+        assert_eq!(evaluate_with_mode(&String::from("√-36"), NumberMode::Decimal, &Context::new()).unwrap_err(), EvalError::DomainError(0));
+        // floating point fallbacks are read as their shortest text
+        assert_eq!(evaluate_with_mode(&String::from("2^0.5"), NumberMode::Decimal, &Context::new()).unwrap(), "1.4142135623730951");
+        assert_eq!(evaluate_with_mode(&String::from("ln(2)"), NumberMode::Decimal, &Context::new()).unwrap(), "0.6931471805599453");
+        // powers too large to work out are outside the domain
+        assert_eq!(evaluate_with_mode(&String::from("2^9999999999"), NumberMode::Decimal, &Context::new()).unwrap_err(), EvalError::DomainError(1));
+        assert_eq!(evaluate_with_mode(&String::from("9^9^9"), NumberMode::Decimal, &Context::new()).unwrap_err(), EvalError::DomainError(1));
+    }
+    #[test]
+    fn previous_answer() {
+        let mut ctx = Context::new();
+        // before any calculation the answer is 0
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("Ans+2"), &ctx).unwrap(), 2.0);
+        ctx.ans = String::from("-1.5");
+        let expr_and_ans = [
+            ("Ans", -1.5),
+            ("Ans×Ans", 2.25),
+            ("2Ans", -3.0),
+            ("√(Ans+17.5)", 4.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1);
+        }
+        // answers from decimal mode can be used in float mode
+        ctx.ans = evaluate_with_mode(&String::from("1÷4"), NumberMode::Decimal, &ctx).unwrap();
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("Ans×2"), &ctx).unwrap(), 0.5);
+    }
+    #[test]
+    fn scientific_functions() {
+        let ctx = Context{angle_mode: AngleMode::Radians, ..Context::new()};
+        let expr_and_ans = [
+            ("sin(π÷2)", 1.0),
+            ("cos(0)+tan(0)", 1.0),
+            ("2asin(1)", std::f64::consts::PI),
+            ("cosh(0)×3", 3.0),
+            ("ln(e^2)", 2.0),
+            ("log10(1000)", 3.0),
+            ("log(2,64)", 6.0),
+            ("log(3,3^(1+1))", 2.0),
+            ("exp(0)", 1.0),
+            ("abs(-4.5)", 4.5),
+            ("floor(-2.5)+ceil(2.1)", 0.0),
+            ("round(2.5)", 3.0),
+            ("5!", 120.0),
+            ("2^3!", 64.0),
+            ("-3!", -6.0),
+            ("sin(0)^2+1", 1.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1);
+        }
+        let expr_and_err = [
+            ("ln(0)", EvalError::DomainError(0)),
+            ("asin(2)", EvalError::DomainError(0)),
+            ("2.5!", EvalError::DomainError(3)),
+            ("log(8)", EvalError::ArgumentCount(0)),
+            ("1,2", EvalError::UnknownSymbol(1))
+        ];
+        for item in expr_and_err {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap_err(), item.1);
+        }
+        // exact functions in decimal mode
+        assert_eq!(evaluate_with_mode(&String::from("25!"), NumberMode::Decimal, &Context::new()).unwrap(), "15511210043330985984000000");
+        assert_eq!(evaluate_with_mode(&String::from("round(-0.5)+floor(1.99)"), NumberMode::Decimal, &Context::new()).unwrap(), "0");
+    }
+    #[test]
+    fn angle_modes() {
+        let mut ctx = Context::new();
+        let mode_expr_and_ans = [
+            (AngleMode::Degrees, "sin(30)", 0.5),
+            (AngleMode::Degrees, "sin(180)+cos(-90)", 0.0),
+            (AngleMode::Degrees, "tan(45)", 1.0),
+            (AngleMode::Degrees, "acos(0)", 90.0),
+            (AngleMode::Radians, "cos(π)", -1.0),
+            (AngleMode::Radians, "atan(1)×4", std::f64::consts::PI),
+            (AngleMode::Gradians, "sin(100)", 1.0),
+            (AngleMode::Gradians, "asin(1)", 100.0)
+        ];
+        for item in mode_expr_and_ans {
+            ctx.angle_mode = item.0;
+            assert!((evaluate_infix_expr::<f64>(&String::from(item.1), &ctx).unwrap() - item.2).abs() < 1e-12);
+        }
+        // tan of a right angle is undefined
+        ctx.angle_mode = AngleMode::Degrees;
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("tan(270)"), &ctx).unwrap_err(), EvalError::DomainError(0));
+        // modes cycle in DRG order
+        assert_eq!(AngleMode::Degrees.next().next().next(), AngleMode::Degrees);
+    }
+    #[test]
+    fn variables() {
+        let mut ctx = Context::new();
+        let statement_and_ans = [
+            ("rate=0.07", "0.07"),
+            ("years=5", "5"),
+            ("principal=1200", "1200"),
+            ("principal×(1+rate)^years", "1683.06207684"),
+            ("x_2=years×2+rate", "10.07"),
+            ("rate=rate×2", "0.14"),
+            ("login=10", "10"),
+            ("log(10,login)", "1")
+        ];
+        for item in statement_and_ans {
+            assert_eq!(evaluate_statement(&String::from(item.0), NumberMode::Decimal, &mut ctx).unwrap(), StatementResult::Answer(String::from(item.1)));
+        }
+        assert_eq!(ctx.variables.get("rate").unwrap(), "0.14");
+        let statement_and_err = [
+            ("unknown+1", EvalError::UnknownSymbol(0)),
+            ("sin=2", EvalError::InvalidAssignment(0)),
+            ("e=2", EvalError::InvalidAssignment(0)),
+            ("2x=2", EvalError::InvalidAssignment(0)),
+            ("y=(1", EvalError::UnbalancedBracket(2)),
+            ("y=1=2", EvalError::UnknownSymbol(3))
+        ];
+        for item in statement_and_err {
+            assert_eq!(evaluate_statement(&String::from(item.0), NumberMode::Decimal, &mut ctx).unwrap_err(), item.1);
+        }
+        // failed assignments leave variables untouched
+        assert!(!ctx.variables.contains_key("y"));
+    }
+    #[test]
+    fn user_functions() {
+        let mut ctx = Context::new();
+        let definitions = [
+            ("f(x,y)=x^2+y", "f"),
+            ("sq(x)=x×x", "sq"),
+            ("hyp(a,b)=√(sq(a)+sq(b))", "hyp"),
+            ("k=10", "")
+        ];
+        for item in definitions {
+            let result = evaluate_statement(&String::from(item.0), NumberMode::Float, &mut ctx).unwrap();
+            if !item.1.is_empty() {
+                assert_eq!(result, StatementResult::Defined(String::from(item.1)));
+            }
+        }
+        let expr_and_ans = [
+            ("f(3,4)", 13.0),
+            ("2f(1,1)+1", 5.0),
+            ("f(sq(2),-k)", 6.0),
+            ("hyp(3,4)", 5.0),
+            ("f(log(2,8),0)", 9.0),
+            // parameters take priority over variables
+            ("f(k,k)", 110.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1);
+        }
+        // functions can't be given the wrong number of inputs
+        let expr_and_err = [
+            ("1+f(3)", EvalError::ArgumentCount(2)),
+            ("f(1,2,3)", EvalError::ArgumentCount(0)),
+            ("sq(1,2)", EvalError::ArgumentCount(0)),
+            ("sin(1,2)", EvalError::ArgumentCount(0)),
+            ("sq", EvalError::ArgumentCount(0)),
+            ("(1,2)", EvalError::UnknownSymbol(2))
+        ];
+        for item in expr_and_err {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap_err(), item.1);
+        }
+        // recursion is stopped once it becomes too deep
+        evaluate_statement(&String::from("r(n)=r(n-1)+1"), NumberMode::Float, &mut ctx).unwrap();
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("2+r(5)"), &ctx).unwrap_err(), EvalError::RecursionLimit(2));
+        // definitions are checked before being stored
+        let statement_and_err = [
+            ("g(x,x)=x", EvalError::InvalidAssignment(0)),
+            ("g(cos)=1", EvalError::InvalidAssignment(0)),
+            ("g(x)=x+y", EvalError::UnknownSymbol(7)),
+            ("g(x)=(x", EvalError::UnbalancedBracket(5))
+        ];
+        for item in statement_and_err {
+            assert_eq!(evaluate_statement(&String::from(item.0), NumberMode::Float, &mut ctx).unwrap_err(), item.1);
+        }
+        assert!(!ctx.functions.contains_key("g"));
+    }
+    #[test]
+    fn token_steps() {
+        let ctx = Context::new();
+        let tokens = parse_tokens::<f64>("-2×(3+log(2,8))!", &ctx).unwrap();
+        let infix: Vec<String> = tokens.iter().map(|token| token.token.to_string()).collect();
+        assert_eq!(infix, ["neg", "2", "×", "(", "3", "+", "log", "(", "2", ",", "8", ")", ")", "!"]);
+        assert_eq!(tokens[6], PositionedToken{token: Token::Operator(Operator::TwoInOperator(TwoInOperator::Log)), offset: 6});
+
+        let postfix = tokens_to_postfix(tokens, &ctx).unwrap();
+        let postfix_text: Vec<String> = postfix.iter().map(|token| token.token.to_string()).collect();
+        assert_eq!(postfix_text, ["2", "neg", "3", "2", "8", "log", "+", "!", "×"]);
+        assert_eq!(evaluate_postfix(&postfix, &ctx).unwrap(), -1440.0);
+
+        // leftover operands are reported where the first extra one starts
+        let mut postfix = infix_to_postfix::<f64>("1+2", &ctx).unwrap();
+        postfix.extend(infix_to_postfix::<f64>("-3×4", &ctx).unwrap().into_iter().map(|token| PositionedToken{offset: token.offset + 4, ..token}));
+        assert_eq!(evaluate_postfix(&postfix, &ctx).unwrap_err(), EvalError::MissingOperator(4));
+
+        // brackets are only checked once converting to postfix
+        assert!(parse_tokens::<f64>("(1+2", &ctx).is_ok());
+        assert_eq!(infix_to_postfix::<f64>("(1+2", &ctx).unwrap_err(), EvalError::UnbalancedBracket(0));
+    }
+    #[test]
+    fn evaluation_trace() {
+        let ctx = Context::new();
+        let working = working_with_mode("2×(3+4)", NumberMode::Decimal, &ctx).unwrap();
+        assert_eq!(working.postfix, ["2", "3", "4", "+", "×"]);
+        let stacks: Vec<Vec<String>> = working.steps.iter().map(|step| step.1.clone()).collect();
+        assert_eq!(stacks, [vec!["2"], vec!["2", "3"], vec!["2", "3", "4"], vec!["2", "7"], vec!["14"]]);
+        assert_eq!(working.result, Ok(String::from("14")));
+
+        // steps before an error are kept
+        let working = working_with_mode("1+ln(0)", NumberMode::Float, &ctx).unwrap();
+        assert_eq!(working.steps.len(), 2);
+        assert_eq!(working.result, Err(EvalError::DomainError(2)));
+    }
+    #[test]
+    fn implied_multiplication() {
+        let mut ctx = Context::new();
+        ctx.variables.insert(String::from("x"), String::from("5"));
+        let expr_and_ans = [
+            ("2(3)+4", 10.0),
+            ("(1+1)(2+3)", 10.0),
+            ("2x", 10.0),
+            ("3x^2", 75.0),
+            ("10÷2x", 25.0),
+            ("2√9", 6.0),
+            ("2abs(-3)", 6.0),
+            ("x(x-1)!", 120.0),
+            ("(2)(3)-1", 5.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1);
+        }
+        // multiplication is written out in the tokens
+        let tokens: Vec<String> = parse_tokens::<f64>("2π(1)", &ctx).unwrap().iter().map(|token| token.token.to_string()).collect();
+        assert_eq!(tokens, ["2", "×", "3.141592653589793", "×", "(", "1", ")"]);
+        // digits can't follow on from another value
+        let expr_and_err = [
+            ("π2", EvalError::MissingOperator(1)),
+            ("(1)2", EvalError::MissingOperator(3)),
+            ("3!2", EvalError::MissingOperator(2))
+        ];
+        for item in expr_and_err {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap_err(), item.1);
+        }
+        // spaces keep numbers apart in plain text
+        assert_eq!(convert_plain_text("2 3"), "2 3");
+        assert_eq!(evaluate_infix_expr::<f64>(&convert_plain_text("2 3"), &ctx).unwrap_err(), EvalError::MissingOperator(2));
+        assert_eq!(convert_plain_text(" 2 * x "), "2×x");
+        assert_eq!(convert_plain_text_with_offsets("2 * (3 +"), (String::from("2×(3+"), vec![0, 2, 4, 5, 7, 8]));
+    }
+    #[test]
+    fn power_and_negative_precedence() {
+        let mut ctx = Context::new();
+        let expr_and_ans = [
+            ("2^3^2", 512.0),
+            ("(2^3)^2", 64.0),
+            ("-2^2", -4.0),
+            ("(-2)^2", 4.0),
+            ("2^-2", 0.25),
+            ("-2^-2", -0.25),
+            ("2^-1^2", 0.5),
+            ("-3×2", -6.0),
+            ("1--2^2", 5.0),
+            ("-3!", -6.0),
+            ("2^2×3", 12.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        // negatives can be set to apply before powers
+        ctx.negation = NegationConvention::Calculator;
+        let expr_and_ans = [
+            ("-2^2", 4.0),
+            ("2^3^2", 512.0),
+            ("2^-2", 0.25),
+            ("1--2^2", -3.0)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1, "{}", item.0);
+        }
+    }
+
+    #[test]
+    fn percentages() {
+        let ctx = Context::new();
+        let expr_and_ans = [
+            ("50%", 0.5),
+            ("200+10%", 220.0),
+            ("200-10%", 180.0),
+            ("200×10%", 20.0),
+            ("200÷10%", 2000.0),
+            ("200+-10%", 180.0),
+            ("(100+100)+10%", 220.0),
+            ("10%+5", 5.1),
+            ("50%%", 0.005),
+            ("5!%", 1.2)
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        assert_eq!(evaluate_with_mode("200+10%", NumberMode::Decimal, &ctx), Ok(String::from("220")));
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("%"), &ctx), Err(EvalError::MissingOperand(0)));
+        assert_eq!(evaluate_infix_expr::<f64>(&String::from("10%2"), &ctx), Err(EvalError::MissingOperator(3)));
+    }
+    #[test]
+    fn complex_numbers() {
+        let mut ctx = Context::new();
+        let expr_and_ans = [
+            ("i", "i"),
+            ("i^2", "-1"),
+            ("√-4+1", "1+2i"),
+            ("(3+4i)×(3-4i)", "25"),
+            ("(1+i)÷(1-i)", "i"),
+            ("-i÷2-3", "-3-0.5i"),
+            ("abs(3-4i)", "5"),
+            ("ln(-1)", "3.141592653589793i"),
+            ("exp(iπ)", "-1"),
+            ("2^3", "8"),
+            ("√(2i)", "1+i"),
+            ("round(1.4-2.6i)", "1-3i")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Complex, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        // answers can be used in later expressions
+        ctx.ans = evaluate_with_mode("2-3i", NumberMode::Complex, &ctx).unwrap();
+        assert_eq!(evaluate_with_mode("Ans×i", NumberMode::Complex, &ctx).unwrap(), "3+2i");
+        // in degrees, complex angles are converted the same as real angles
+        ctx.angle_mode = AngleMode::Degrees;
+        assert_eq!(evaluate_with_mode("sin(90)+cos(90i)", NumberMode::Complex, &ctx).unwrap(), evaluate_with_mode("1+cosh(π÷2)", NumberMode::Complex, &ctx).unwrap());
+        // i is only a number when using complex numbers
+        assert_eq!(evaluate_with_mode("2i", NumberMode::Float, &ctx).unwrap_err(), EvalError::UnknownSymbol(1));
+        assert_eq!(evaluate_with_mode("2i", NumberMode::Decimal, &ctx).unwrap_err(), EvalError::UnknownSymbol(1));
+        assert_eq!(evaluate_with_mode("1÷(i-i)", NumberMode::Complex, &ctx).unwrap_err(), EvalError::DomainError(1));
+    }
+    #[test]
+    fn programmer_mode() {
+        let mut ctx = Context::new();
+        let expr_and_ans = [
+            ("0xff", "255"),
+            ("0o17+0b101", "20"),
+            ("0xff&¬0x0f", "240"),
+            ("0b1100|0b1010", "14"),
+            ("0b1100⊕0b1010", "6"),
+            ("12xor10", "6"),
+            ("1«4+1", "32"),
+            ("-16»2", "-4"),
+            ("7mod3", "1"),
+            ("-7mod3", "-1"),
+            ("7÷2", "3"),
+            ("1|2&3⊕1", "3"),
+            ("2^63", "-9223372036854775808"),
+            ("2^64", "0")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Programmer, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        // results wrap around within the word size
+        let size_and_ans = [
+            (8, false, "255+1", "0"),
+            (8, false, "0-1", "255"),
+            (8, true, "127+1", "-128"),
+            (8, true, "0xff", "-1"),
+            (16, false, "¬0", "65535"),
+            (16, true, "200×200", "-25536"),
+            (32, false, "1«32", "0"),
+            (32, true, "-1»40", "-1"),
+            (64, false, "-1", "18446744073709551615")
+        ];
+        for item in size_and_ans {
+            ctx.word_size = WordSize{bits: item.0, signed: item.1};
+            assert_eq!(evaluate_with_mode(item.2, NumberMode::Programmer, &ctx).unwrap(), item.3, "{}", item.2);
+        }
+        // other modes have modulo but not bitwise operators
+        assert_eq!(evaluate_with_mode("7.5mod2", NumberMode::Decimal, &ctx).unwrap(), "1.5");
+        assert_eq!(evaluate_with_mode("0xff", NumberMode::Float, &ctx).unwrap(), "255");
+        assert_eq!(evaluate_with_mode("5&3", NumberMode::Float, &ctx).unwrap_err(), EvalError::DomainError(1));
+        assert_eq!(evaluate_with_mode("1.5", NumberMode::Programmer, &ctx).unwrap_err(), EvalError::MalformedNumber(0));
+        assert_eq!(evaluate_with_mode("5mod0", NumberMode::Programmer, &ctx).unwrap_err(), EvalError::DomainError(1));
+        // shifts can be written with one or two chars in plain text
+        assert_eq!(convert_plain_text("1<<4|256>>4"), "1«4|256»4");
+        assert_eq!(convert_plain_text("1<4"), "1«4");
+        assert!(is_repeated_shift(Some('«'), '«'));
+        assert!(!is_repeated_shift(Some('«'), '»'));
+    }
+    #[test]
+    fn rational_numbers() {
+        let mut ctx = Context::new();
+        let expr_and_ans = [
+            ("1÷3×3", "1"),
+            ("1÷4+1÷3", "7÷12"),
+            ("0.1+0.2", "3÷10"),
+            ("-2.5", "-5÷2"),
+            ("(2÷3)^-2", "9÷4"),
+            ("(4÷9)^0.5", "2÷3"),
+            ("√(9÷16)", "3÷4"),
+            ("(-8)^(1÷3)", "-2"),
+            ("7÷2mod1", "1÷2"),
+            ("floor(-7÷2)+ceil(7÷2)", "0"),
+            ("round(-5÷2)", "-3"),
+            ("30!÷29!", "30")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Rational, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        // fractions are kept exactly between calculations
+        ctx.ans = evaluate_with_mode("1÷7", NumberMode::Rational, &ctx).unwrap();
+        assert_eq!(evaluate_with_mode("Ans×7", NumberMode::Rational, &ctx).unwrap(), "1");
+        assert_eq!(evaluate_with_mode("1÷(1÷3-1÷3)", NumberMode::Rational, &ctx).unwrap_err(), EvalError::DomainError(1));
+        // powers too large to work out are outside the domain
+        assert_eq!(evaluate_with_mode("(10^10000)^10000", NumberMode::Rational, &ctx).unwrap_err(), EvalError::DomainError(10));
+        assert_eq!(evaluate_with_mode("0.1^100000", NumberMode::Rational, &ctx).unwrap_err(), EvalError::DomainError(3));
 
+        let fraction = Rational::parse_literal("-13÷12").unwrap();
+        assert_eq!(fraction.fraction_string(), "-13/12");
+        assert_eq!(fraction.mixed_string(), "-1 1/12");
+        assert_eq!(fraction.decimal_string(), "-1.0833333333333333333");
+        assert_eq!(Rational::parse_literal("7÷12").unwrap().mixed_string(), "7/12");
+    }
+    #[test]
+    fn units() {
+        let mut ctx = Context::new();
+        let expr_and_ans = [
+            ("5 km to m", "5000 m"),
+            ("1 mi in km", "1.609344 km"),
+            ("2km+300m", "2300 m"),
+            ("3m×4m", "12 m^2"),
+            ("√(9m^2)", "3 m"),
+            ("10m÷(2s)", "5 m×s^-1"),
+            ("100 km÷h to m÷s", "27.77777777777778 m÷s"),
+            ("1 GiB to MB", "1073.741824 MB"),
+            ("1 min+30 s to s", "90 s"),
+            ("2kg+10%", "2.2 kg"),
+            ("2 km÷(500 m)", "4")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Float, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        assert_eq!(evaluate_with_mode("1 ft to inch", NumberMode::Decimal, &ctx).unwrap(), "12 inch");
+        // temperatures after a number are taken on the unit's scale
+        let expr_and_ans = [
+            ("20°C to °F", "68 °F"),
+            ("-40 degF to degC", "-40 degC"),
+            ("100°C-90°C", "10 K")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Rational, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        assert_eq!(evaluate_with_mode("0°C to K", NumberMode::Float, &ctx).unwrap(), "273.15 K");
+        // answers keep their units
+        ctx.ans = evaluate_with_mode("5 km to mi", NumberMode::Rational, &ctx).unwrap();
+        assert_eq!(evaluate_with_mode("Ans to m", NumberMode::Rational, &ctx).unwrap(), "5000 m");
+        ctx.variables.insert(String::from("speed"), String::from("10 m÷s"));
+        assert_eq!(evaluate_with_mode("speed×1 min", NumberMode::Float, &ctx).unwrap(), "600 m");
+        // units must match when added or converted
+        let expr_and_err = [
+            ("5 km+2 kg", EvalError::DimensionMismatch(4)),
+            ("5 km to s", EvalError::DimensionMismatch(5)),
+            ("3 to 4", EvalError::DimensionMismatch(2)),
+            ("sin(2m)", EvalError::DimensionMismatch(0)),
+            ("2^(3m)", EvalError::DimensionMismatch(1)),
+            ("5 km to", EvalError::MissingOperand(5)),
+            ("(5 km to m)", EvalError::UnknownSymbol(6))
+        ];
+        for item in expr_and_err {
+            assert_eq!(evaluate_infix_expr::<f64>(&String::from(item.0), &ctx).unwrap_err(), item.1, "{}", item.0);
+        }
+        assert_eq!(convert_plain_text("5 km  to mi * 2"), "5 km to mi×2");
+        // working is shown for the part before a conversion
+        let working = working_with_mode("6 km to m", NumberMode::Decimal, &ctx).unwrap();
+        assert_eq!(working.postfix, ["6", "1000 m", "×"]);
+        assert_eq!(working.result, Ok(String::from("6000 m")));
+        // function bodies can end in a conversion
+        assert_eq!(evaluate_statement("half(x)=x÷2 to m", NumberMode::Float, &mut ctx).unwrap(), StatementResult::Defined(String::from("half")));
+        assert_eq!(evaluate_with_mode("half(2 km)×2 to km", NumberMode::Float, &ctx).unwrap(), "2 km");
+        assert_eq!(evaluate_statement("k(x)=x to", NumberMode::Float, &mut ctx).unwrap_err(), EvalError::MissingOperand(7));
+        // units before brackets read as calls to undefined functions
+        assert_eq!(evaluate_with_mode("h(10 m)", NumberMode::Float, &ctx).unwrap_err(), EvalError::UnknownSymbol(0));
+    }
+    #[test]
+    fn currencies() {
+        let mut ctx = Context::new();
+        ctx.currencies = CurrencyRates::parse("# rates\nupdated,2026-10-01\nUSD,1\nEUR,0.8\nGBP,0.5\n").unwrap();
+        assert_eq!(ctx.currencies.updated, Some(String::from("2026-10-01")));
+        let expr_and_ans = [
+            ("100 USD to EUR", "80 EUR"),
+            ("10 GBP in EUR", "16 EUR"),
+            ("40 EUR+10 USD", "60 USD"),
+            ("3 EUR÷(2 kg)", "1.875 USD×kg^-1"),
+            ("10 EUR÷(4 EUR)", "2.5")
+        ];
+        for item in expr_and_ans {
+            assert_eq!(evaluate_with_mode(item.0, NumberMode::Decimal, &ctx).unwrap(), item.1, "{}", item.0);
+        }
+        // answers keep their currency
+        ctx.ans = evaluate_with_mode("8 EUR", NumberMode::Decimal, &ctx).unwrap();
+        assert_eq!(evaluate_with_mode("Ans to GBP", NumberMode::Decimal, &ctx).unwrap(), "5 GBP");
+        assert_eq!(evaluate_with_mode("5 USD to kg", NumberMode::Decimal, &ctx).unwrap_err(), EvalError::DimensionMismatch(6));
+        assert_eq!(evaluate_with_mode("5 XYZ", NumberMode::Decimal, &ctx).unwrap_err(), EvalError::UnknownSymbol(2));
+        // amounts of money can be given to functions
+        evaluate_statement("f(x)=x×2", NumberMode::Decimal, &mut ctx).unwrap();
+        assert_eq!(evaluate_with_mode("f(5 USD)", NumberMode::Decimal, &ctx).unwrap(), "10 USD");
+        assert_eq!(evaluate_with_mode("f(4 EUR) to EUR", NumberMode::Decimal, &ctx).unwrap(), "8 EUR");
+        // function bodies can end in a conversion
+        assert_eq!(evaluate_statement("g(x)=x to EUR", NumberMode::Decimal, &mut ctx).unwrap(), StatementResult::Defined(String::from("g")));
+        assert_eq!(evaluate_with_mode("g(10 USD)×2 to EUR", NumberMode::Decimal, &ctx).unwrap(), "16 EUR");
+        // currencies before brackets read as calls to undefined functions
+        assert_eq!(evaluate_with_mode("h(10 USD)", NumberMode::Decimal, &ctx).unwrap_err(), EvalError::UnknownSymbol(0));
+        // problems with the rates file are found by line
+        let text_and_err = [
+            ("USD,1\nEUR", RatesError::MalformedLine(2)),
+            ("usd,1", RatesError::InvalidCode(1)),
+            ("USD,1\n\nEUR,-2", RatesError::InvalidRate(3)),
+            ("USD,1\nEUR,1e3", RatesError::InvalidRate(2)),
+            ("USD,+5", RatesError::InvalidRate(1)),
+            ("USD,inf", RatesError::InvalidRate(1)),
+            ("USD,0", RatesError::InvalidRate(1)),
+            ("USD,1\nUSD,2", RatesError::DuplicateCode(2)),
+            ("updated,today", RatesError::Empty)
+        ];
+        for item in text_and_err {
+            assert_eq!(CurrencyRates::parse(item.0).unwrap_err(), item.1, "{}", item.0);
+        }
+    }
+}
\ No newline at end of file
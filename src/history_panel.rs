@@ -1,22 +1,61 @@
 /// The history panel of the calculator
 
 use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::number_format::{self, NumberFormat};
 const FONT_SIZE: f32 = 32.0;
 
 // Stores a past calculation
+#[derive(Serialize, Deserialize)]
 pub struct Calculation {
     pub expression: String,
-    pub answer: String
+    pub answer: String,
+    // Whether the answer is an error message rather than a number
+    #[serde(default)]
+    pub is_error: bool,
+    // Whether the answer is a note of a function being defined
+    #[serde(default)]
+    pub is_definition: bool
 }
 
-/// Displays the vec of Calculations given in a list format
-pub fn show_calculations(ui: &mut egui::Ui, calcs: &Vec<Calculation>) {
+// Part of a past calculation that was clicked on
+pub enum HistoryClick {
+    Expression(String),
+    Answer(String)
+}
+
+/// Displays the vec of Calculations given in a list format,
+/// with answers shown in the number format given.
+/// 
+/// Returns the part of a Calculation that was clicked (if any).
+pub fn show_calculations(ui: &mut egui::Ui, calcs: &Vec<Calculation>, format: &NumberFormat) -> Option<HistoryClick> {
+    let mut clicked: Option<HistoryClick> = None;
     // Set vertical scrollable panel
     egui::ScrollArea::vertical().show(ui, |ui| {
         for calc in calcs {
-            ui.label(egui::RichText::new(&calc.expression).size(FONT_SIZE));
-            ui.strong(egui::RichText::new(&calc.answer).size(FONT_SIZE));
+            // Clicking an expression brings it back for editing
+            if ui.add(egui::Label::new(egui::RichText::new(&calc.expression).size(FONT_SIZE))
+                .sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked() {
+                clicked = Some(HistoryClick::Expression(calc.expression.clone()));
+            }
+            // Clicking an answer adds it onto the current expression
+            // in full, errors and definitions have no answer to add
+            let answer = match calc.is_error || calc.is_definition {
+                true => calc.answer.clone(),
+                false => number_format::format_number(&calc.answer, format)
+            };
+            let answer_label = egui::Label::new(egui::RichText::new(answer).size(FONT_SIZE).strong());
+            if calc.is_error || calc.is_definition {
+                ui.add(answer_label);
+            } else if ui.add(answer_label.sense(egui::Sense::click()))
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .clicked() {
+                clicked = Some(HistoryClick::Answer(calc.answer.clone()));
+            }
             ui.separator();
         } 
     });
-}
\ No newline at end of file
+    return clicked;
+}
@@ -0,0 +1,87 @@
+/// Keyboard input for the calculator.
+/// Special keys are translated into the same texts as the
+/// calculator buttons so they can be handled identically.
+
+use eframe::egui;
+use crate::expression_evaluate::{convert_plain_text, convert_plain_char, is_repeated_shift};
+
+// Input read from the keyboard
+pub enum KeyInput {
+    // Same as pressing the calculator button with this text
+    Press(&'static str),
+    // Text to add onto the expression
+    Text(String)
+}
+
+/// Converts a typed char into the char used within expressions.
+/// 
+/// Returns None if the char has no use in the calculator.
+fn convert_typed_char(ch: char) -> Option<char> {
+    match ch {
+        '0'..='9' | '.' | '+' | '-' | '^' | '(' | ')' | ',' | '!' | '%' => return Some(ch),
+        // letters for typing names of functions, values and variables
+        'a'..='z' | 'A'..='Z' | '_' => return Some(ch),
+        // spaces keep apart names e.g "5 km to mi"
+        ' ' => return Some(ch),
+        // assignment of variables
+        '=' => return Some(ch),
+        // bitwise operators
+        '&' | '|' => return Some(ch),
+        // operators mapped as in pasted text
+        '*' | '/' | '~' | '<' | '>' => return Some(convert_plain_char(ch)),
+        _ => return None
+    }
+}
+
+/// Reads this frame's keyboard events (in the order they happened),
+/// unless another widget has keyboard focus.
+/// `expression` is the text being typed onto, so that shifts typed
+/// as "<<" are read as one shift, as they are when pasted.
+pub fn read_key_inputs(ctx: &egui::Context, expression: &str) -> Vec<KeyInput> {
+    let mut inputs: Vec<KeyInput> = Vec::new();
+    // keys typed into another widget (e.g a settings field) are its own
+    if ctx.wants_keyboard_input() {
+        return inputs;
+    }
+    // last char of the expression after the inputs so far
+    let mut last_char = expression.chars().last();
+    for event in ctx.input().events.iter() {
+        match event {
+            egui::Event::Text(text) => {
+                let mut converted = String::new();
+                for ch in text.chars().filter_map(convert_typed_char) {
+                    if !is_repeated_shift(last_char, ch) {
+                        converted.push(ch);
+                        last_char = Some(ch);
+                    }
+                }
+                if !converted.is_empty() {
+                    inputs.push(KeyInput::Text(converted));
+                }
+            }
+            egui::Event::Key{key, pressed: true, ..} => {
+                let press = match key {
+                    egui::Key::Enter => "=",
+                    egui::Key::Backspace => "⌫",
+                    egui::Key::Escape => "C",
+                    _ => continue
+                };
+                inputs.push(KeyInput::Press(press));
+                // the expression's last char is no longer known
+                last_char = None;
+            }
+            egui::Event::Paste(text) => {
+                let mut converted = convert_plain_text(text);
+                if converted.chars().next().is_some_and(|ch| is_repeated_shift(last_char, ch)) {
+                    converted.remove(0);
+                }
+                if !converted.is_empty() {
+                    last_char = converted.chars().last();
+                    inputs.push(KeyInput::Text(converted));
+                }
+            }
+            _ => ()
+        }
+    }
+    return inputs;
+}
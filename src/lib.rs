@@ -0,0 +1,33 @@
+// The calculator's evaluation engine, shared by the GUI app and
+// the command line evaluator. Builds without any GUI dependencies
+// when the "gui" feature is turned off.
+//
+// Evaluating an expression goes through these steps, each of which
+// can be used on its own:
+//  - parse_tokens: reads infix text into Tokens
+//  - tokens_to_postfix: orders the Tokens into Postfix
+//  - evaluate_postfix: evaluates the Postfix into a number
+//    (trace_postfix also gives the working stack after each Token)
+// infix_to_postfix and evaluate_infix_expr combine the steps, while
+// evaluate_statement also handles variable assignments and function
+// definitions. syntax_tree gives the structure of an expression as a
+// tree instead, while units gives the units that numbers can be written in
+// and currency reads the exchange rates used to convert currencies.
+// number_format formats answers for display.
+pub mod expression_evaluate;
+pub mod syntax_tree;
+pub mod numeric;
+pub mod units;
+pub mod currency;
+pub mod number_format;
+
+pub use expression_evaluate::{
+    parse_tokens, tokens_to_postfix, infix_to_postfix, evaluate_postfix, trace_postfix,
+    working_with_mode, evaluate_infix_expr, evaluate_quantity, operator_from_symbol, evaluate_with_mode, evaluate_statement, convert_plain_text, convert_plain_text_with_offsets,
+    Token, PositionedToken, Postfix, EvalStep, Working, Operator, OneInOperator, TwoInOperator, Bracket,
+    Context, AngleMode, NegationConvention, UserFunction, StatementResult, EvalError
+};
+pub use units::{Dimension, Quantity, Unit};
+pub use currency::{CurrencyRates, RatesError};
+pub use number_format::{format_number, Notation, NumberFormat};
+pub use numeric::{Numeric, NumberMode, decimal::Decimal, rational::Rational, complex::Complex, word::{Word, WordSize}};
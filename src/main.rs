@@ -3,31 +3,247 @@
 #![windows_subsystem = "windows"]
 
 use eframe::{run_native, epi::App, egui};
-mod expression_evaluate;
+use basic_calculator::{expression_evaluate, numeric, currency, number_format};
+use expression_evaluate::{StatementResult, Operator};
+use numeric::NumberMode;
+use settings_bar::{EntryMode, FractionForm};
 mod button_layout;
 mod number_display;
 mod history_panel;
+mod variables_panel;
+mod working_panel;
+mod rpn_entry;
+mod keyboard_input;
+mod settings_bar;
 mod fonts;
 
 const NUM_DISPLAY_MIN_HEIGHT: f32 = 85.0;
 const MIN_WINDOW_X: f32 = 450.0;
 const MIN_WINDOW_Y: f32 = 400.0;
+// Storage keys for saving state between restarts
+const HISTORY_KEY: &str = "history";
+const EXPRESSION_KEY: &str = "expression";
+const SETTINGS_KEY: &str = "settings";
+const CONTEXT_KEY: &str = "context";
+const MEMORY_KEY: &str = "memory";
+const RPN_KEY: &str = "rpn";
 
 struct Calculator {
     curr_expression: String,
+    // Message of the most recent evaluation error (if any)
+    error_message: Option<String>,
     past_entries: Vec<history_panel::Calculation>,
+    // Values expressions can refer to (e.g Ans)
+    context: expression_evaluate::Context,
+    // Memory register used by the M buttons
+    memory: String,
+    // Working of the most recent evaluation (if shown)
+    working: Option<expression_evaluate::Working>,
+    // Stack used when entering in RPN
+    rpn: rpn_entry::RpnStack,
+    // Why the exchange rates couldn't be read (if they couldn't)
+    rates_error: Option<String>,
     button_area: button_layout::CalculatorButtons,
+    settings: settings_bar::Settings,
+    settings_bar_height: f32,
     num_display_height: f32
 }
 impl Calculator {
     pub fn new() -> Self {
         return Self {
             curr_expression: String::new(),
+            error_message: None,
             past_entries: Vec::new(),
+            context: expression_evaluate::Context::new(),
+            memory: String::from("0"),
+            working: None,
+            rpn: rpn_entry::RpnStack::new(),
+            rates_error: None,
             button_area: button_layout::CalculatorButtons::new(),
+            settings: settings_bar::Settings::new(),
+            settings_bar_height: 0.0,
             num_display_height: 0.0
         };
     }
+
+    /// Evaluates the current expression (or variable assignment or function
+    /// definition) and records it into the history.
+    /// 
+    /// Returns the answer, or None if there was an error or nothing to answer.
+    fn evaluate_expression(&mut self) -> Option<String> {
+        let expression: String = self.curr_expression.clone();
+        if self.settings.show_working {
+            self.working = Self::find_working(&expression, self.settings.number_mode, &self.context);
+        }
+        
+        match expression_evaluate::evaluate_statement(&self.curr_expression, self.settings.number_mode, &mut self.context) {
+            Ok(StatementResult::Defined(name)) => { // Note the definition in history and clear for the next expression
+                self.curr_expression.clear();
+                self.past_entries.push(history_panel::Calculation{expression, answer: format!("Defined {}", name), is_error: false, is_definition: true});
+                return None;
+            }
+            Ok(StatementResult::Answer(answer)) => { // For no error, add expression and result into history
+                self.curr_expression = answer.clone();
+                self.context.ans = answer.clone();
+                self.past_entries.push(history_panel::Calculation{expression, answer: answer.clone(), is_error: false, is_definition: false});
+                return Some(answer);
+            }
+            Err(error) => { // On error, keep expression for fixing and place error into history
+                self.past_entries.push(history_panel::Calculation{expression, answer: format!("Error: {}", error), is_error: true, is_definition: false});
+                self.error_message = Some(error.to_string());
+                return None;
+            }
+        }
+    }
+
+    /// Gives the working of a statement's evaluation, or None if it is a
+    /// function definition or can't be converted to Postfix.
+    /// Assignments give the working of the assigned expression.
+    fn find_working(statement: &str, mode: NumberMode, context: &expression_evaluate::Context) -> Option<expression_evaluate::Working> {
+        let expression = match statement.split_once('=') {
+            None => statement,
+            Some((head, body)) if !head.contains('(') => body,
+            Some(_) => return None
+        };
+        return expression_evaluate::working_with_mode(expression, mode, context).ok();
+    }
+
+    /// Adds ("+") or subtracts ("-") a value to the memory register.
+    fn add_to_memory(&mut self, sign: &str, value: &str) {
+        let memory_expression = format!("{}{}({})", self.memory, sign, value);
+        match expression_evaluate::evaluate_with_mode(&memory_expression, self.settings.number_mode, &self.context) {
+            Ok(memory) => self.memory = memory,
+            Err(error) => self.error_message = Some(error.to_string())
+        }
+    }
+
+    /// Carries out the behaviour of a calculator button press.
+    /// Typed keys are translated into the same presses.
+    fn handle_press(&mut self, press: &str) {
+        // Any new press dismisses the previous error
+        self.error_message = None;
+        if self.settings.entry_mode == EntryMode::Rpn {
+            self.handle_rpn_press(press);
+            return;
+        }
+        match press {
+            "=" => {let _ = self.evaluate_expression();}, // Evaluate expression
+            "M+" | "M-" => { // Add/subtract answer of expression to memory
+                if let Some(answer) = self.evaluate_expression() {
+                    self.add_to_memory(&press[1..], &answer);
+                }
+            },
+            "MR" => self.curr_expression.push_str(&self.memory), // Recall memory onto expression
+            "MC" => self.memory = String::from("0"), // Clear memory
+            "DRG" => self.context.angle_mode = self.context.angle_mode.next(), // Cycle angle mode
+            "C" => self.curr_expression.clear(), // Clear expression
+            "⌫" => self.backspace(),
+            // Push function name with its open bracket onto expression
+            _ if expression_evaluate::FUNCTION_NAMES.contains(&press) => {
+                self.curr_expression.push_str(press);
+                self.curr_expression.push('(');
+            },
+            _ => self.curr_expression.push_str(press) // Push number/operator onto expression
+        }
+    }
+
+    /// Carries out a button press while entering in RPN. Operators
+    /// apply straight to the stack and are recorded into the history.
+    fn handle_rpn_press(&mut self, press: &str) {
+        let mode = self.settings.number_mode;
+        // Calculation done on the stack (if any) and its result
+        let result: Result<Option<(String, String)>, expression_evaluate::EvalError> = match press {
+            "ENTER" | "=" => self.rpn.enter(mode, &self.context).map(|_| None),
+            "SWAP" => self.rpn.swap(mode, &self.context).map(|_| None),
+            "DROP" => self.rpn.drop(mode, &self.context).map(|_| None),
+            "ROLL" => self.rpn.roll(mode, &self.context).map(|_| None),
+            "±" => self.rpn.change_sign(mode, &self.context),
+            "M+" | "M-" => { // Add/subtract top of the stack to memory
+                self.rpn.top(mode, &self.context).map(|top| {
+                    self.add_to_memory(&press[1..], &top);
+                    None
+                })
+            },
+            "MR" => self.rpn.push(self.memory.clone(), mode, &self.context).map(|_| None), // Recall memory onto stack
+            "MC" => {
+                self.memory = String::from("0");
+                Ok(None)
+            },
+            "DRG" => {
+                self.context.angle_mode = self.context.angle_mode.next();
+                Ok(None)
+            },
+            "C" => {
+                self.rpn.clear();
+                Ok(None)
+            },
+            "⌫" => {
+                self.rpn.backspace();
+                Ok(None)
+            },
+            _ => {
+                match expression_evaluate::operator_from_symbol(press) {
+                    // Apply operators straight onto the stack
+                    Some(op @ (Operator::OneInOperator(_) | Operator::TwoInOperator(_))) => self.rpn.apply(&op, mode, &self.context).map(Some),
+                    // Anything else is part of the number being entered
+                    _ => {
+                        self.rpn.entry.push_str(press);
+                        Ok(None)
+                    }
+                }
+            }
+        };
+        match result {
+            Ok(Some((calculation, answer))) => {
+                self.context.ans = answer.clone();
+                self.past_entries.push(history_panel::Calculation{expression: calculation, answer, is_error: false, is_definition: false});
+            }
+            Ok(None) => (),
+            Err(error) => self.error_message = Some(String::from(error.description()))
+        }
+    }
+
+    /// Adds text onto the end of the expression (or the number
+    /// being entered in RPN)
+    fn insert_text(&mut self, text: &str) {
+        self.error_message = None;
+        if self.settings.entry_mode == EntryMode::Rpn {
+            // Typed operators apply to the stack
+            if text.chars().count() == 1 && expression_evaluate::operator_from_symbol(text).is_some() {
+                self.handle_rpn_press(text);
+            } else {
+                self.rpn.entry.push_str(text);
+            }
+            return;
+        }
+        self.curr_expression.push_str(text);
+    }
+
+    /// Removes the last char of the expression. Names which
+    /// span multiple chars (e.g "sin") are removed all at once.
+    fn backspace(&mut self) {
+        let names = expression_evaluate::FUNCTION_NAMES.iter()
+            .chain(expression_evaluate::OPERATOR_NAMES.iter())
+            .chain(expression_evaluate::VALUE_NAMES.iter());
+        let mut remove_len: usize = 1;
+        for name in names {
+            if self.curr_expression.ends_with(name) && name.chars().count() > remove_len {
+                remove_len = name.chars().count();
+            }
+        }
+        for _ in 0..remove_len {
+            let _ = self.curr_expression.pop();
+        }
+    }
+
+    /// Removes the oldest calculations until the history
+    /// is within the maximum length set
+    fn trim_history(&mut self) {
+        if self.past_entries.len() > self.settings.max_history {
+            let excess = self.past_entries.len() - self.settings.max_history;
+            self.past_entries.drain(..excess);
+        }
+    }
 }
 
 impl App for Calculator {
@@ -36,10 +252,46 @@ impl App for Calculator {
         &mut self,
         ctx: &egui::Context, 
         _frame: &eframe::epi::Frame,
-        _storage: Option<&dyn eframe::epi::Storage>
+        storage: Option<&dyn eframe::epi::Storage>
     ) {
         fonts::set_font(ctx);
         ctx.set_visuals(egui::Visuals::dark());
+        // Restore state from previous run
+        if let Some(storage) = storage {
+            if let Some(settings) = eframe::epi::get_value(storage, SETTINGS_KEY) {
+                self.settings = settings;
+            }
+            if let Some(past_entries) = eframe::epi::get_value(storage, HISTORY_KEY) {
+                self.past_entries = past_entries;
+                self.trim_history();
+            }
+            if let Some(expression) = eframe::epi::get_value(storage, EXPRESSION_KEY) {
+                self.curr_expression = expression;
+            }
+            if let Some(context) = eframe::epi::get_value(storage, CONTEXT_KEY) {
+                self.context = context;
+            }
+            if let Some(memory) = eframe::epi::get_value(storage, MEMORY_KEY) {
+                self.memory = memory;
+            }
+            if let Some(rpn) = eframe::epi::get_value(storage, RPN_KEY) {
+                self.rpn = rpn;
+            }
+        }
+        // Exchange rates are read from the rates file on every start
+        match currency::CurrencyRates::load(&currency::CurrencyRates::file_path()) {
+            Ok(rates) => self.context.currencies = rates,
+            Err(error) => self.rates_error = Some(error.to_string())
+        }
+    }
+    // Save state for the next run
+    fn save(&mut self, storage: &mut dyn eframe::epi::Storage) {
+        eframe::epi::set_value(storage, SETTINGS_KEY, &self.settings);
+        eframe::epi::set_value(storage, HISTORY_KEY, &self.past_entries);
+        eframe::epi::set_value(storage, EXPRESSION_KEY, &self.curr_expression);
+        eframe::epi::set_value(storage, CONTEXT_KEY, &self.context);
+        eframe::epi::set_value(storage, MEMORY_KEY, &self.memory);
+        eframe::epi::set_value(storage, RPN_KEY, &self.rpn);
     }
     // for each frame
     fn update(
@@ -53,6 +305,25 @@ impl App for Calculator {
                 + self.button_area.height
             );
 
+        // Keyboard behaviour
+        let typed_onto = match self.settings.entry_mode {
+            EntryMode::Algebraic => self.curr_expression.clone(),
+            EntryMode::Rpn => self.rpn.entry.clone()
+        };
+        for input in keyboard_input::read_key_inputs(ctx, &typed_onto) {
+            match input {
+                keyboard_input::KeyInput::Press(press) => self.handle_press(press),
+                keyboard_input::KeyInput::Text(text) => self.insert_text(&text)
+            }
+        }
+
+        // Settings along the very top
+        self.settings_bar_height = egui::TopBottomPanel::top("settings_bar")
+        .show(ctx, |ui| {
+            settings_bar::show_settings_bar(ui, &mut self.settings, &mut self.context.negation, &mut self.context.word_size, &self.context.currencies, &self.rates_error);
+        }).response.rect.height();
+        self.trim_history();
+
         // Bottom area
         egui::TopBottomPanel::bottom("main_area")
         .max_height(self.button_area.height)
@@ -62,43 +333,89 @@ impl App for Calculator {
             .frame(egui::Frame::none())
             .width_range(button_layout::MIN_WIDTH_NEEDED ..= expansion)
             .show_inside(ui, |ui| {
+                self.button_area.rpn_entry = self.settings.entry_mode == EntryMode::Rpn;
+                self.button_area.programmer = self.settings.number_mode == NumberMode::Programmer;
                 self.button_area.show_buttons(ui);
                 // Button behaviour
-                match self.button_area.recent_press.as_deref() {
-                    None => (),
-                    Some("=") => { // Evaluate expression
-                        let potential_answer: Option<f64> = expression_evaluate::evaluate_infix_expr(&self.curr_expression); 
-                        let expression: String = self.curr_expression.clone();
-                        
-                        if potential_answer.is_some() { // For no error, add expression and result into history
-                            self.curr_expression = potential_answer.unwrap().to_string();
-                            self.past_entries.push(history_panel::Calculation{expression: expression, answer: self.curr_expression.clone()})
-                        }
-                        else {
-                            self.curr_expression.clear(); // On error, clear number display and place error into history
-                            self.past_entries.push(history_panel::Calculation{expression: expression, answer: String::from("Error")});
-                        }
-                    },
-                    Some("C") => self.curr_expression.clear(), // Clear expression
-                    Some("⌫") => {let _ = self.curr_expression.pop();}, // Backspace
-                    _ => self.curr_expression.push_str(self.button_area.recent_press.as_ref().unwrap().as_str()) // Push number/operator onto expression
+                if let Some(press) = self.button_area.recent_press.clone() {
+                    self.handle_press(&press);
                 }
             });
 
+            // List variables between the buttons and history (if there are any)
+            if !self.context.variables.is_empty() || !self.context.functions.is_empty() {
+                egui::SidePanel::right("variables")
+                .show_inside(ui, |ui| {
+                    match variables_panel::show_variables(ui, &self.context.variables, &self.context.functions) {
+                        None => (),
+                        Some(variables_panel::VariableAction::Insert(name)) => self.insert_text(&name),
+                        Some(variables_panel::VariableAction::Remove(name)) => {
+                            self.context.variables.remove(&name);
+                            self.context.functions.remove(&name);
+                        }
+                    }
+                });
+            }
+
+            // Show how the last answer was reached (if turned on)
+            if self.settings.show_working {
+                egui::SidePanel::right("working")
+                .show_inside(ui, |ui| {
+                    working_panel::show_working(ui, &self.working);
+                });
+            }
+
             // Fill up rest of bottom left with list of past calculations
             egui::CentralPanel::default().show_inside(ui, |ui| {
                 // ui.set_min_width(RIGHT_PANEL_MIN_WIDTH);
-                history_panel::show_calculations(ui, &self.past_entries);
+                match history_panel::show_calculations(ui, &self.past_entries, &self.settings.number_format) {
+                    None => (),
+                    // Load expression back for editing
+                    Some(history_panel::HistoryClick::Expression(expression)) => {
+                        self.error_message = None;
+                        self.curr_expression = expression;
+                    }
+                    // Continue on from a past answer
+                    Some(history_panel::HistoryClick::Answer(answer)) => self.insert_text(&answer)
+                }
             });
         });
 
         // Fill up rest of top section with the answer display
         egui::CentralPanel::default()
         .show(ctx, |ui| {
-            number_display::show_number_screen(ui, &self.curr_expression);
+            let indicators = [self.context.angle_mode.name()];
+            // Fractions and complex numbers are shown in the chosen
+            // form and programmer integers in the chosen base,
+            // decimal answers in the chosen number format
+            let format = &self.settings.number_format;
+            let shown = |text: &String| match self.settings.number_mode {
+                NumberMode::Rational => match self.settings.fraction_form {
+                    FractionForm::Decimal => number_format::format_number(&number_display::fraction_text(text, FractionForm::Decimal), format),
+                    form => number_display::fraction_text(text, form)
+                },
+                NumberMode::Complex => number_display::complex_text(text, self.settings.complex_form, self.context.angle_mode),
+                NumberMode::Programmer => number_display::programmer_text(text, self.settings.display_base, self.context.word_size),
+                _ => number_format::format_number(text, format)
+            };
+            match self.settings.entry_mode {
+                EntryMode::Algebraic => {
+                    // only answers are converted, not numbers being typed
+                    let text = match self.curr_expression == self.context.ans {
+                        true => shown(&self.curr_expression),
+                        false => self.curr_expression.clone()
+                    };
+                    number_display::show_number_screen(ui, &text, &self.error_message, &indicators)
+                }
+                // The stack takes the place of the expression
+                EntryMode::Rpn => {
+                    let stack: Vec<String> = self.rpn.stack.iter().map(shown).collect();
+                    number_display::show_stack_screen(ui, &stack, &self.rpn.entry, &self.error_message, &indicators)
+                }
+            }
         });
 
-        self.num_display_height = ctx.used_size().y - self.button_area.height;
+        self.num_display_height = ctx.used_size().y - self.button_area.height - self.settings_bar_height;
     }
     // Name of application
     fn name(&self) -> &str { 
@@ -1,16 +1,117 @@
 /// The number display of the calculator
 
 use eframe::egui;
+use crate::expression_evaluate::AngleMode;
+use crate::numeric::{Numeric, rational::Rational, complex::Complex, word::WordSize};
+use crate::settings_bar::{Base, ComplexForm, FractionForm};
 const FONT_SIZE: f32 = 48.0;
+const ERROR_FONT_SIZE: f32 = 20.0;
+const ERROR_COLOUR: egui::Color32 = egui::Color32::from_rgb(235, 87, 87);
+const INDICATOR_FONT_SIZE: f32 = 16.0;
+const INDICATOR_SPACING: f32 = 8.0;
+const STACK_FONT_SIZE: f32 = 28.0;
 
-/// Place label onto the UI where numbers will be displayed
-pub fn show_number_screen(ui: &mut egui::Ui, text: &String) {
+/// Gives the text to display for a complex number in the given form,
+/// with polar angles in the given angle mode (e.g 3+4i is 5∠53.13...
+/// in degrees). Text which isn't a single number (e.g an expression
+/// being written) is shown as it is.
+pub fn complex_text(text: &str, form: ComplexForm, angle_mode: AngleMode) -> String {
+    let num = match Complex::parse_literal(text) {
+        Some(num) => num,
+        None => return String::from(text)
+    };
+    match form {
+        ComplexForm::Rectangular => return num.to_string(),
+        ComplexForm::Polar => {
+            let angle = angle_mode.convert_from_radians(num.argument());
+            return format!("{}∠{}", num.modulus(), angle + 0.0);
+        }
+    }
+}
+
+/// Gives the text to display for a fraction in the given form
+/// (e.g 13÷12 is 13/12, 1 1/12 or 1.083...). Text which isn't a single
+/// fraction (e.g an expression being written) is shown as it is.
+pub fn fraction_text(text: &str, form: FractionForm) -> String {
+    let num = match Rational::parse_literal(text) {
+        Some(num) => num,
+        None => return String::from(text)
+    };
+    match form {
+        FractionForm::Fraction => return num.fraction_string(),
+        FractionForm::Mixed => return num.mixed_string(),
+        FractionForm::Decimal => return num.decimal_string()
+    }
+}
+
+/// Gives the text to display for an integer in the given base, with
+/// negative numbers shown by their bits within the word size (e.g -1
+/// is 0xFF for 8-bit words). Text which isn't a single integer is
+/// shown as it is.
+pub fn programmer_text(text: &str, base: Base, word_size: WordSize) -> String {
+    let value = match text.parse::<i128>() {
+        Ok(value) => word_size.to_unsigned(value),
+        Err(_) => return String::from(text)
+    };
+    match base {
+        Base::Decimal => return String::from(text),
+        Base::Hexadecimal => return format!("0x{:X}", value),
+        Base::Octal => return format!("0o{:o}", value),
+        Base::Binary => return format!("0b{:b}", value)
+    }
+}
+
+/// Place label onto the UI where numbers will be displayed.
+/// Indicators of the calculator's state (e.g angle mode) are shown
+/// on the top left and an error message (if given) on the top right.
+pub fn show_number_screen(ui: &mut egui::Ui, text: &String, error: &Option<String>, indicators: &[&str]) {
     let number_area = egui::RichText::new(text).size(FONT_SIZE).strong();
     ui.spacing_mut().item_spacing = egui::vec2(0.0,0.0);
+    show_status_row(ui, error, indicators);
     // Vertical scrollable are incase of large numbers
     egui::ScrollArea::vertical().show(ui, |ui| {
         ui.with_layout(egui::Layout::right_to_left(), |ui| {
             ui.add(egui::Label::new(number_area).wrap(true));
         });
     });
+}
+
+/// Place the RPN stack onto the UI in place of the number display.
+/// Each level of the stack is numbered (1 being the top) with the
+/// number being entered shown below them.
+pub fn show_stack_screen(ui: &mut egui::Ui, stack: &[String], entry: &String, error: &Option<String>, indicators: &[&str]) {
+    ui.spacing_mut().item_spacing = egui::vec2(0.0,0.0);
+    show_status_row(ui, error, indicators);
+    // Vertical scrollable area which keeps the top of the stack in view
+    egui::ScrollArea::vertical().stick_to_bottom().show(ui, |ui| {
+        for (i, value) in stack.iter().enumerate() {
+            let level = stack.len() - i;
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{}:", level)).size(INDICATOR_FONT_SIZE).weak());
+                ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                    ui.add(egui::Label::new(egui::RichText::new(value).size(STACK_FONT_SIZE)).wrap(true));
+                });
+            });
+        }
+        if !entry.is_empty() {
+            ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                ui.add(egui::Label::new(egui::RichText::new(entry).size(FONT_SIZE).strong()).wrap(true));
+            });
+        }
+    });
+}
+
+/// Shows the indicators and error message along the top of the display.
+fn show_status_row(ui: &mut egui::Ui, error: &Option<String>, indicators: &[&str]) {
+    ui.horizontal(|ui| {
+        for indicator in indicators {
+            ui.label(egui::RichText::new(*indicator).size(INDICATOR_FONT_SIZE).weak());
+            ui.add_space(INDICATOR_SPACING);
+        }
+        if let Some(message) = error {
+            ui.with_layout(egui::Layout::right_to_left(), |ui| {
+                ui.label(egui::RichText::new(message).size(ERROR_FONT_SIZE).color(ERROR_COLOUR));
+            });
+        }
+    });
 }
\ No newline at end of file
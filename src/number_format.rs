@@ -0,0 +1,260 @@
+/// Formatting of answers for display, with a chosen notation, number
+/// of digits and thousands separators (e.g "1.23×10^21" or "1,234.50").
+/// Answers keep their full text so formatting only changes how they are shown.
+
+use std::num::NonZeroU64;
+use std::str::FromStr;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::{Deserialize, Serialize};
+
+// How numbers are written
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Notation {
+    // Plain digits, switching to scientific when too long
+    Auto,
+    // A set number of decimal places e.g 3.14
+    Fixed,
+    // A set number of significant figures e.g 3.142
+    Significant,
+    // One digit before the point and a power of ten e.g 1.5×10^3
+    Scientific,
+    // Powers of ten that are multiples of three e.g 15×10^3
+    Engineering
+}
+impl Notation {
+    pub const ALL: [Notation; 5] = [Notation::Auto, Notation::Fixed, Notation::Significant, Notation::Scientific, Notation::Engineering];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Notation::Auto => return "Auto",
+            Notation::Fixed => return "Fixed",
+            Notation::Significant => return "Significant",
+            Notation::Scientific => return "Scientific",
+            Notation::Engineering => return "Engineering"
+        }
+    }
+
+    /// Whether the notation uses a chosen number of digits
+    pub fn uses_digits(&self) -> bool {
+        return *self != Notation::Auto;
+    }
+}
+
+/// Options for how numbers are shown.
+/// Missing fields in saved settings are filled with defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberFormat {
+    pub notation: Notation,
+    // Decimal places for Fixed, significant figures for the others
+    pub digits: usize,
+    // Whether the whole part is split into groups of three e.g 1,000,000
+    pub thousands_separators: bool,
+    // Most significant digits shown before rounding,
+    // or switching to scientific notation for large numbers
+    pub max_digits: usize
+}
+impl NumberFormat {
+    pub fn new() -> Self {
+        return Self {
+            notation: Notation::Auto,
+            digits: 6,
+            thousands_separators: false,
+            max_digits: 16
+        };
+    }
+}
+impl Default for NumberFormat {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+// Digits of a number rounded to some significant figures, along
+// with its sign and the power of ten of its first digit
+// e.g -1234.5 to 3 figures is (true, "123", 3)
+struct Digits {
+    negative: bool,
+    digits: String,
+    exponent: i64
+}
+
+/// Rounds a non-zero number to the given significant figures,
+/// padding with zeros if it has fewer.
+fn significant_digits(number: &BigDecimal, figures: usize) -> Digits {
+    let figures = NonZeroU64::new(figures.max(1) as u64).unwrap_or(NonZeroU64::MIN);
+    let rounded = number.with_precision_round(figures, RoundingMode::HalfUp);
+    let (integer, scale) = rounded.as_bigint_and_exponent();
+    let negative = integer < Zero::zero();
+    let mut digits = integer.magnitude().to_string();
+    let exponent = digits.len() as i64 - 1 - scale;
+    while (digits.len() as u64) < figures.get() {
+        digits.push('0');
+    }
+    return Digits{negative, digits, exponent};
+}
+
+/// Splits the whole part of a plain number into groups of three
+/// e.g "-1234567.5" gives "-1,234,567.5"
+fn group_thousands(text: &str) -> String {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", text)
+    };
+    let (whole, fraction) = match unsigned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (unsigned, None)
+    };
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    return match fraction {
+        Some(fraction) => format!("{}{}.{}", sign, grouped, fraction),
+        None => format!("{}{}", sign, grouped)
+    };
+}
+
+/// Writes digits with the given number of them before the point,
+/// padding the whole part with zeros if needed.
+fn point_after(digits: &str, whole_digits: i64) -> String {
+    if whole_digits <= 0 {
+        return format!("0.{}{}", "0".repeat(-whole_digits as usize), digits);
+    }
+    let whole_digits = whole_digits as usize;
+    if digits.len() <= whole_digits {
+        return format!("{}{}", digits, "0".repeat(whole_digits - digits.len()));
+    }
+    return format!("{}.{}", &digits[..whole_digits], &digits[whole_digits..]);
+}
+
+/// Writes rounded digits as plain digits e.g 1234.5
+fn plain_text(digits: &Digits) -> String {
+    let sign = if digits.negative { "-" } else { "" };
+    return format!("{}{}", sign, point_after(&digits.digits, digits.exponent + 1));
+}
+
+/// Writes rounded digits with the power of ten as a multiple of `step`,
+/// 1 for scientific and 3 for engineering notation.
+fn power_text(digits: &Digits, step: i64) -> String {
+    let sign = if digits.negative { "-" } else { "" };
+    let shift = digits.exponent.rem_euclid(step);
+    let mantissa = point_after(&digits.digits, shift + 1);
+    return match digits.exponent - shift {
+        0 => format!("{}{}", sign, mantissa),
+        exponent => format!("{}{}×10^{}", sign, mantissa, exponent)
+    };
+}
+
+/// Formats a number in text form for display. Text after the number
+/// (e.g units) is kept as it is, as is text that isn't a decimal number
+/// (e.g fractions or complex numbers).
+pub fn format_number(text: &str, format: &NumberFormat) -> String {
+    let (number_text, rest) = match text.find(' ') {
+        Some(index) => text.split_at(index),
+        None => (text, "")
+    };
+    let number = match BigDecimal::from_str(number_text) {
+        Ok(number) => number,
+        Err(_) => return String::from(text)
+    };
+    let max_digits = format.max_digits.max(1);
+    let digits = format.digits.min(max_digits);
+    let formatted = if number.is_zero() {
+        match format.notation {
+            Notation::Fixed => BigDecimal::zero().with_scale(digits as i64).to_plain_string(),
+            _ => String::from("0")
+        }
+    } else {
+        match format.notation {
+            Notation::Auto => {
+                let mut rounded = significant_digits(&number, max_digits);
+                let trimmed_length = rounded.digits.trim_end_matches('0').len().max(1);
+                rounded.digits.truncate(trimmed_length);
+                match -(max_digits as i64) < rounded.exponent && rounded.exponent < max_digits as i64 {
+                    true => plain_text(&rounded),
+                    false => power_text(&rounded, 1)
+                }
+            }
+            Notation::Fixed => {
+                let rounded = number.with_scale_round(digits as i64, RoundingMode::HalfUp);
+                // Too many whole digits to show
+                match rounded.digits() as usize > max_digits + digits {
+                    true => power_text(&significant_digits(&number, max_digits), 1),
+                    false => rounded.to_plain_string()
+                }
+            }
+            Notation::Significant => {
+                let rounded = significant_digits(&number, digits);
+                match rounded.exponent < max_digits as i64 && rounded.exponent > -(max_digits as i64) {
+                    true => plain_text(&rounded),
+                    false => power_text(&rounded, 1)
+                }
+            }
+            Notation::Scientific => power_text(&significant_digits(&number, digits), 1),
+            Notation::Engineering => power_text(&significant_digits(&number, digits), 3)
+        }
+    };
+    // Mantissas are short enough to need no separators
+    let formatted = match format.thousands_separators && !formatted.contains('×') {
+        true => group_thousands(&formatted),
+        false => formatted
+    };
+    return format!("{}{}", formatted, rest);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(notation: Notation, digits: usize) -> NumberFormat {
+        return NumberFormat{notation, digits, ..NumberFormat::new()};
+    }
+
+    #[test]
+    fn auto() {
+        let format = NumberFormat::new();
+        assert_eq!(format_number("0.30000000000000004", &format), "0.3");
+        assert_eq!(format_number("1e21", &format), "1×10^21");
+        assert_eq!(format_number("-1234.5", &format), "-1234.5");
+        assert_eq!(format_number("0.000012", &format), "0.000012");
+        assert_eq!(format_number("91.999999999999999999997 EUR", &format), "92 EUR");
+        assert_eq!(format_number("0", &format), "0");
+        let format = NumberFormat{max_digits: 4, ..format};
+        assert_eq!(format_number("123456", &format), "1.235×10^5");
+        assert_eq!(format_number("3.14159", &format), "3.142");
+    }
+
+    #[test]
+    fn notations() {
+        assert_eq!(format_number("3.14159", &with(Notation::Fixed, 2)), "3.14");
+        assert_eq!(format_number("2.5", &with(Notation::Fixed, 3)), "2.500");
+        assert_eq!(format_number("0", &with(Notation::Fixed, 2)), "0.00");
+        assert_eq!(format_number("1e30", &with(Notation::Fixed, 2)), "1.000000000000000×10^30");
+        assert_eq!(format_number("1234.5678", &with(Notation::Significant, 3)), "1230");
+        assert_eq!(format_number("0.0012345", &with(Notation::Significant, 2)), "0.0012");
+        assert_eq!(format_number("2.5", &with(Notation::Significant, 3)), "2.50");
+        assert_eq!(format_number("123456", &with(Notation::Scientific, 3)), "1.23×10^5");
+        assert_eq!(format_number("-0.00045", &with(Notation::Scientific, 2)), "-4.5×10^-4");
+        assert_eq!(format_number("123456", &with(Notation::Engineering, 3)), "123×10^3");
+        assert_eq!(format_number("0.0123", &with(Notation::Engineering, 3)), "12.3×10^-3");
+        assert_eq!(format_number("1234", &with(Notation::Engineering, 2)), "1.2×10^3");
+        // text that isn't a decimal number is left alone
+        assert_eq!(format_number("13/12", &with(Notation::Fixed, 2)), "13/12");
+        assert_eq!(format_number("3+4i", &with(Notation::Fixed, 2)), "3+4i");
+    }
+
+    #[test]
+    fn thousands_separators() {
+        let format = NumberFormat{thousands_separators: true, ..NumberFormat::new()};
+        assert_eq!(format_number("1234567.25", &format), "1,234,567.25");
+        assert_eq!(format_number("-123456", &format), "-123,456");
+        assert_eq!(format_number("999", &format), "999");
+        assert_eq!(format_number("1500 m", &format), "1,500 m");
+        let format = NumberFormat{notation: Notation::Fixed, digits: 2, ..format};
+        assert_eq!(format_number("1000000", &format), "1,000,000.00");
+    }
+}
@@ -0,0 +1,249 @@
+// Complex numbers made of two f64 parts.
+// Unlike f64, operations such as √-36 give an answer (6i)
+// rather than NaN. Real inputs give the same answers as f64.
+use std::fmt;
+use super::Numeric;
+
+// Parts smaller than this fraction of the other part are shown as
+// zero, hiding rounding errors such as exp(iπ) = -1+1.2e-16i
+const NEGLIGIBLE: f64 = 1e-14;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64
+}
+
+impl Complex {
+    /// Returns None if either part isn't finite
+    fn new(re: f64, im: f64) -> Option<Self> {
+        if !re.is_finite() || !im.is_finite() {
+            return None;
+        }
+        return Some(Complex{re, im});
+    }
+
+    fn is_real(&self) -> bool {
+        return self.im == 0.0;
+    }
+
+    /// Distance from zero
+    pub fn modulus(&self) -> f64 {
+        return self.re.hypot(self.im);
+    }
+    /// Angle from the positive real axis in radians (-π to π).
+    /// Negative real numbers have an angle of π.
+    pub fn argument(&self) -> f64 {
+        // adding zero turns -0 into 0, which would give -π
+        return (self.im + 0.0).atan2(self.re);
+    }
+
+    /// Gives the number with the given modulus and argument (in radians)
+    fn from_polar(modulus: f64, argument: f64) -> Option<Self> {
+        return Self::new(modulus * argument.cos(), modulus * argument.sin());
+    }
+}
+
+impl fmt::Display for Complex {
+    // Shown in rectangular form e.g "3-4i", leaving out zero parts
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // adding zero turns -0 into 0
+        let mut re = self.re + 0.0;
+        let mut im = self.im + 0.0;
+        if im.abs() < re.abs() * NEGLIGIBLE {
+            im = 0.0;
+        }
+        if re.abs() < im.abs() * NEGLIGIBLE {
+            re = 0.0;
+        }
+        // a unit imaginary part is written as just i
+        let im_text = match im.abs() == 1.0 {
+            true => String::from(if im > 0.0 {"i"} else {"-i"}),
+            false => format!("{}i", im)
+        };
+        if im == 0.0 {
+            return write!(f, "{}", re);
+        }
+        if re == 0.0 {
+            return write!(f, "{}", im_text);
+        }
+        if im > 0.0 {
+            return write!(f, "{}+{}", re, im_text);
+        }
+        return write!(f, "{}{}", re, im_text);
+    }
+}
+
+impl Numeric for Complex {
+    // Reads real numbers (e.g "12.5") as well as complex numbers
+    // in the form they are displayed (e.g "i", "6i" or "3-4i")
+    fn parse_literal(text: &str) -> Option<Self> {
+        let imaginary = match text.strip_suffix('i') {
+            Some(imaginary) => imaginary,
+            None => return Self::new(text.parse::<f64>().ok()?, 0.0)
+        };
+        // the imaginary part starts at the last sign (besides
+        // signs at the start or within exponents)
+        let chars: Vec<char> = imaginary.chars().collect();
+        let split = (1..chars.len()).rev()
+            .find(|i| matches!(chars[*i], '+' | '-') && chars[*i - 1] != 'e')
+            .unwrap_or(0);
+        let split_byte = imaginary.char_indices().nth(split).map(|(byte, _)| byte).unwrap_or(0);
+        let (re_text, im_text) = imaginary.split_at(split_byte);
+        let re = match re_text {
+            "" => 0.0,
+            _ => re_text.parse::<f64>().ok()?
+        };
+        let im = match im_text {
+            "" | "+" => 1.0,
+            "-" => -1.0,
+            _ => im_text.parse::<f64>().ok()?
+        };
+        return Self::new(re, im);
+    }
+
+    // Only real numbers can be converted
+    fn to_f64(&self) -> Option<f64> {
+        if !self.is_real() {
+            return None;
+        }
+        return Some(self.re);
+    }
+    fn from_f64(num: f64) -> Option<Self> {
+        return Self::new(num, 0.0);
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        return Self::new(self.re + other.re, self.im + other.im);
+    }
+    fn subtract(&self, other: &Self) -> Option<Self> {
+        return Self::new(self.re - other.re, self.im - other.im);
+    }
+    fn multiply(&self, other: &Self) -> Option<Self> {
+        // real numbers are multiplied alone so no NaN arises from 0×inf
+        if self.is_real() && other.is_real() {
+            return Self::new(self.re * other.re, 0.0);
+        }
+        return Self::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re);
+    }
+    fn divide(&self, other: &Self) -> Option<Self> {
+        if other.re == 0.0 && other.im == 0.0 {
+            return None;
+        }
+        if other.is_real() {
+            return Self::new(self.re / other.re, self.im / other.re);
+        }
+        let denominator = other.re * other.re + other.im * other.im;
+        return Self::new((self.re * other.re + self.im * other.im) / denominator, (self.im * other.re - self.re * other.im) / denominator);
+    }
+    fn power(&self, exponent: &Self) -> Option<Self> {
+        // Positive real numbers to real powers are done as for f64
+        if self.is_real() && exponent.is_real() && self.re >= 0.0 {
+            return Self::new(self.re.powf(exponent.re), 0.0);
+        }
+        // Integer powers are done by repeated multiplication
+        // so answers like i^2 = -1 are exact
+        if exponent.is_real() && exponent.re.fract() == 0.0 && exponent.re.abs() <= i32::MAX as f64 {
+            let mut remaining = exponent.re.abs() as u32;
+            let mut result = Complex{re: 1.0, im: 0.0};
+            let mut square = *self;
+            while remaining > 0 {
+                if remaining % 2 == 1 {
+                    result = result.multiply(&square)?;
+                }
+                remaining /= 2;
+                if remaining > 0 {
+                    square = square.multiply(&square)?;
+                }
+            }
+            if exponent.re < 0.0 {
+                return Complex{re: 1.0, im: 0.0}.divide(&result);
+            }
+            return Some(result);
+        }
+        // Otherwise z^w = exp(w ln z)
+        if self.re == 0.0 && self.im == 0.0 {
+            if exponent.re > 0.0 {
+                return Self::new(0.0, 0.0);
+            }
+            return None;
+        }
+        return exponent.multiply(&self.ln()?)?.exp();
+    }
+    // Principal square root, so negative real numbers
+    // give positive imaginary numbers e.g √-36 = 6i
+    fn square_root(&self) -> Option<Self> {
+        if self.is_real() && self.re >= 0.0 {
+            return Self::new(self.re.sqrt(), 0.0);
+        }
+        let modulus = self.modulus();
+        let re = ((modulus + self.re) / 2.0).sqrt();
+        let im = ((modulus - self.re) / 2.0).sqrt();
+        if self.im < 0.0 {
+            return Self::new(re, -im);
+        }
+        return Self::new(re, im);
+    }
+    fn negative(&self) -> Option<Self> {
+        return Self::new(-self.re, -self.im);
+    }
+
+    // Size of the number (its modulus)
+    fn abs(&self) -> Option<Self> {
+        return Self::new(self.modulus(), 0.0);
+    }
+    // Rounding functions round each part
+    fn floor(&self) -> Option<Self> {
+        return Self::new(self.re.floor(), self.im.floor());
+    }
+    fn ceil(&self) -> Option<Self> {
+        return Self::new(self.re.ceil(), self.im.ceil());
+    }
+    fn round(&self) -> Option<Self> {
+        return Self::new(self.re.round(), self.im.round());
+    }
+
+    fn exp(&self) -> Option<Self> {
+        return Self::from_polar(self.re.exp(), self.im);
+    }
+    // Principal logarithm, so negative real numbers
+    // give an imaginary part of π e.g ln(-1) = πi
+    fn ln(&self) -> Option<Self> {
+        if self.re == 0.0 && self.im == 0.0 {
+            return None;
+        }
+        if self.is_real() && self.re > 0.0 {
+            return Self::new(self.re.ln(), 0.0);
+        }
+        return Self::new(self.modulus().ln(), self.argument());
+    }
+    fn log10(&self) -> Option<Self> {
+        if self.is_real() && self.re > 0.0 {
+            return Self::new(self.re.log10(), 0.0);
+        }
+        return self.ln()?.divide(&Complex{re: std::f64::consts::LN_10, im: 0.0});
+    }
+    fn log(&self, base: &Self) -> Option<Self> {
+        return self.ln()?.divide(&base.ln()?);
+    }
+
+    // Angles are in radians
+    fn sin(&self) -> Option<Self> {
+        return Self::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh());
+    }
+    fn cos(&self) -> Option<Self> {
+        return Self::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh());
+    }
+    fn tan(&self) -> Option<Self> {
+        return self.sin()?.divide(&self.cos()?);
+    }
+    fn sinh(&self) -> Option<Self> {
+        return Self::new(self.re.sinh() * self.im.cos(), self.re.cosh() * self.im.sin());
+    }
+    fn cosh(&self) -> Option<Self> {
+        return Self::new(self.re.cosh() * self.im.cos(), self.re.sinh() * self.im.sin());
+    }
+    fn tanh(&self) -> Option<Self> {
+        return self.sinh()?.divide(&self.cosh()?);
+    }
+}
@@ -0,0 +1,154 @@
+// Base 10 arbitrary precision numbers.
+// Unlike f64, numbers such as 0.1 are stored exactly so
+// calculations like 0.1+0.2 come out as 0.3
+use std::fmt;
+use std::str::FromStr;
+use bigdecimal::{BigDecimal, One, RoundingMode, Signed, ToPrimitive, Zero};
+use bigdecimal::num_bigint::BigInt;
+use super::{Numeric, MAX_FACTORIAL};
+
+// Number of significant digits kept for results that can't be
+// represented exactly (e.g 1÷3 or √2)
+const PRECISION: u64 = 50;
+// Significant digits kept between the steps of working out a power,
+// a few more than PRECISION so rounding errors don't reach the result
+const WORKING_PRECISION: u64 = PRECISION + 10;
+// Largest power of ten of results of powers, larger (or smaller)
+// results are taken as outside the domain
+const MAX_POWER_MAGNITUDE: f64 = 10000.0;
+
+/// Raises a number to an integer power by repeated squaring,
+/// rounding each step so the number of digits can't grow without limit
+fn rounded_power(base: &BigDecimal, exponent: i64) -> BigDecimal {
+    let mut result = BigDecimal::one();
+    let mut square = base.clone();
+    let mut remaining = exponent.unsigned_abs();
+    while remaining > 0 {
+        if remaining % 2 == 1 {
+            result = (&result * &square).with_prec(WORKING_PRECISION);
+        }
+        remaining /= 2;
+        if remaining > 0 {
+            square = (&square * &square).with_prec(WORKING_PRECISION);
+        }
+    }
+    if exponent < 0 {
+        return (BigDecimal::one() / result).with_prec(PRECISION);
+    }
+    return result.with_prec(PRECISION);
+}
+
+/// Power of ten of a non-zero number's size e.g 2 for 100
+fn magnitude(num: &BigDecimal) -> f64 {
+    match num.abs().to_f64().filter(|num| num.is_finite() && *num > 0.0) {
+        Some(num) => return num.log10(),
+        // Too large or small for f64, so estimated from the digits
+        None => return (num.digits() as i64 - num.fractional_digit_count()) as f64
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal(BigDecimal);
+
+impl fmt::Display for Decimal {
+    // Always display in plain notation with no trailing zeros
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.0.normalized().to_plain_string());
+    }
+}
+
+impl Numeric for Decimal {
+    fn parse_literal(text: &str) -> Option<Self> {
+        return BigDecimal::from_str(text).ok().map(Decimal);
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        return self.0.to_f64();
+    }
+    // Read from the shortest text giving the same f64, rather than its
+    // exact binary value (e.g 0.1 rather than 0.1000000000000000055...)
+    fn from_f64(num: f64) -> Option<Self> {
+        if !num.is_finite() {
+            return None;
+        }
+        return Self::parse_literal(&num.to_string());
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        return Some(Decimal(&self.0 + &other.0));
+    }
+    fn subtract(&self, other: &Self) -> Option<Self> {
+        return Some(Decimal(&self.0 - &other.0));
+    }
+    fn multiply(&self, other: &Self) -> Option<Self> {
+        return Some(Decimal(&self.0 * &other.0));
+    }
+    fn divide(&self, other: &Self) -> Option<Self> {
+        if other.0.is_zero() {
+            return None;
+        }
+        return Some(Decimal((&self.0 / &other.0).with_prec(PRECISION)));
+    }
+    fn power(&self, exponent: &Self) -> Option<Self> {
+        // Integer powers can be done exactly
+        if exponent.0.is_integer() {
+            let exponent_int = exponent.0.to_i64()?;
+            if self.0.is_zero() {
+                return match exponent_int {
+                    0 => Some(Decimal(BigDecimal::one())),
+                    1.. => Some(Decimal(BigDecimal::zero())),
+                    _ => None
+                };
+            }
+            // Check the size of the result before working it out
+            if (magnitude(&self.0) * exponent_int as f64).abs() > MAX_POWER_MAGNITUDE {
+                return None;
+            }
+            return Some(Decimal(rounded_power(&self.0, exponent_int)));
+        }
+        // Otherwise fallback to floating point
+        let result = self.0.to_f64()?.powf(exponent.0.to_f64()?);
+        if !result.is_finite() {
+            return None;
+        }
+        return Self::from_f64(result);
+    }
+    fn square_root(&self) -> Option<Self> {
+        return self.0.sqrt().map(|root| Decimal(root.with_prec(PRECISION)));
+    }
+    fn negative(&self) -> Option<Self> {
+        return Some(Decimal(-&self.0));
+    }
+    fn modulo(&self, other: &Self) -> Option<Self> {
+        if other.0.is_zero() {
+            return None;
+        }
+        return Some(Decimal(&self.0 % &other.0));
+    }
+
+    // Rounding functions can be done exactly
+    fn abs(&self) -> Option<Self> {
+        return Some(Decimal(self.0.abs()));
+    }
+    fn floor(&self) -> Option<Self> {
+        return Some(Decimal(self.0.with_scale_round(0, RoundingMode::Floor)));
+    }
+    fn ceil(&self) -> Option<Self> {
+        return Some(Decimal(self.0.with_scale_round(0, RoundingMode::Ceiling)));
+    }
+    fn round(&self) -> Option<Self> {
+        return Some(Decimal(self.0.with_scale_round(0, RoundingMode::HalfUp)));
+    }
+    // Factorials are done exactly with big integers
+    fn factorial(&self) -> Option<Self> {
+        if !self.0.is_integer() || self.0.is_negative() {
+            return None;
+        }
+        let num = self.0.to_u32().filter(|num| *num <= MAX_FACTORIAL)?;
+        let mut result = BigInt::from(1);
+        for i in 2..=num {
+            result *= i;
+        }
+        return Some(Decimal(BigDecimal::from(result)));
+    }
+}
@@ -0,0 +1,215 @@
+// Number types that expressions can be evaluated with.
+// Any type implementing Numeric can be used by the evaluator in
+// expression_evaluate, NumberMode picks which one is used at runtime.
+use std::fmt;
+use serde::{Deserialize, Serialize};
+
+pub mod decimal;
+pub mod complex;
+pub mod rational;
+pub mod word;
+
+// Largest input accepted by factorial
+const MAX_FACTORIAL: u32 = 1000;
+
+/// Arithmetic needed by the evaluator.
+/// 
+/// Operations return None when the inputs are outside
+/// of the operation's domain (e.g dividing by zero).
+/// Functions without an exact implementation for a type
+/// fall back to being computed with f64.
+pub trait Numeric: Clone + fmt::Display {
+    /// Parse a string of decimal digits (e.g "12.5")
+    /// 
+    /// Returns None if the text is not a valid number.
+    fn parse_literal(text: &str) -> Option<Self>;
+
+    /// Conversions to and from f64.
+    /// Returns None if the number can't be represented.
+    fn to_f64(&self) -> Option<f64>;
+    fn from_f64(num: f64) -> Option<Self>;
+
+    /// Applies a function on f64 by converting to and from f64.
+    /// Results which aren't finite are taken as outside the domain.
+    fn map_f64<F: Fn(f64) -> f64>(&self, function: F) -> Option<Self> {
+        let result = function(self.to_f64()?);
+        if !result.is_finite() {
+            return None;
+        }
+        return Self::from_f64(result);
+    }
+
+    fn add(&self, other: &Self) -> Option<Self>;
+    fn subtract(&self, other: &Self) -> Option<Self>;
+    fn multiply(&self, other: &Self) -> Option<Self>;
+    fn divide(&self, other: &Self) -> Option<Self>;
+    fn power(&self, exponent: &Self) -> Option<Self>;
+    fn square_root(&self) -> Option<Self>;
+    fn negative(&self) -> Option<Self>;
+
+    fn abs(&self) -> Option<Self> {
+        return self.map_f64(f64::abs);
+    }
+    fn floor(&self) -> Option<Self> {
+        return self.map_f64(f64::floor);
+    }
+    fn ceil(&self) -> Option<Self> {
+        return self.map_f64(f64::ceil);
+    }
+    // Halfway cases are rounded away from zero
+    fn round(&self) -> Option<Self> {
+        return self.map_f64(f64::round);
+    }
+    // Only defined for non-negative integers
+    fn factorial(&self) -> Option<Self> {
+        return self.map_f64(float_factorial);
+    }
+    fn exp(&self) -> Option<Self> {
+        return self.map_f64(f64::exp);
+    }
+    fn ln(&self) -> Option<Self> {
+        return self.map_f64(f64::ln);
+    }
+    fn log10(&self) -> Option<Self> {
+        return self.map_f64(f64::log10);
+    }
+    // Trigonometric functions take angles in radians
+    fn sin(&self) -> Option<Self> {
+        return self.map_f64(f64::sin);
+    }
+    fn cos(&self) -> Option<Self> {
+        return self.map_f64(f64::cos);
+    }
+    fn tan(&self) -> Option<Self> {
+        return self.map_f64(f64::tan);
+    }
+    fn sinh(&self) -> Option<Self> {
+        return self.map_f64(f64::sinh);
+    }
+    fn cosh(&self) -> Option<Self> {
+        return self.map_f64(f64::cosh);
+    }
+    fn tanh(&self) -> Option<Self> {
+        return self.map_f64(f64::tanh);
+    }
+    /// Remainder of dividing by other, with the sign of the number
+    /// (e.g -7 mod 3 = -1)
+    fn modulo(&self, _other: &Self) -> Option<Self> {
+        return None;
+    }
+    // Bitwise operations, only defined for integer types
+    fn bit_and(&self, _other: &Self) -> Option<Self> {
+        return None;
+    }
+    fn bit_or(&self, _other: &Self) -> Option<Self> {
+        return None;
+    }
+    fn bit_xor(&self, _other: &Self) -> Option<Self> {
+        return None;
+    }
+    fn bit_not(&self) -> Option<Self> {
+        return None;
+    }
+    fn shift_left(&self, _amount: &Self) -> Option<Self> {
+        return None;
+    }
+    fn shift_right(&self, _amount: &Self) -> Option<Self> {
+        return None;
+    }
+    /// Logarithm of the number using the given base
+    fn log(&self, base: &Self) -> Option<Self> {
+        let result = self.to_f64()?.ln() / base.to_f64()?.ln();
+        if !result.is_finite() {
+            return None;
+        }
+        return Self::from_f64(result);
+    }
+}
+
+/// Factorial of a f64. Gives NaN if not a non-negative integer
+/// and infinity if the result is too large.
+fn float_factorial(num: f64) -> f64 {
+    if num < 0.0 || num.fract() != 0.0 {
+        return f64::NAN;
+    }
+    if num > MAX_FACTORIAL as f64 {
+        return f64::INFINITY;
+    }
+    let mut result: f64 = 1.0;
+    for i in 2..=(num as u32) {
+        result *= i as f64;
+    }
+    return result;
+}
+
+// Standard floating point numbers.
+impl Numeric for f64 {
+    fn parse_literal(text: &str) -> Option<Self> {
+        return text.parse::<f64>().ok();
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        return Some(*self);
+    }
+    fn from_f64(num: f64) -> Option<Self> {
+        return Some(num);
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        return Some(self + other);
+    }
+    fn subtract(&self, other: &Self) -> Option<Self> {
+        return Some(self - other);
+    }
+    fn multiply(&self, other: &Self) -> Option<Self> {
+        return Some(self * other);
+    }
+    fn divide(&self, other: &Self) -> Option<Self> {
+        if *other == 0.0 {
+            return None;
+        }
+        return Some(self / other);
+    }
+    fn power(&self, exponent: &Self) -> Option<Self> {
+        return Some(self.powf(*exponent));
+    }
+    // Square roots of negatives are left as NaN
+    fn square_root(&self) -> Option<Self> {
+        return Some(self.sqrt());
+    }
+    fn negative(&self) -> Option<Self> {
+        return Some(-self);
+    }
+    fn modulo(&self, other: &Self) -> Option<Self> {
+        if *other == 0.0 {
+            return None;
+        }
+        return Some(self % other);
+    }
+}
+
+/// The number types selectable for evaluation
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NumberMode {
+    Float,
+    Decimal,
+    // Exact fractions
+    Rational,
+    Complex,
+    // Fixed size integers, with the size kept in the Context
+    Programmer
+}
+impl NumberMode {
+    pub const ALL: [NumberMode; 5] = [NumberMode::Float, NumberMode::Decimal, NumberMode::Rational, NumberMode::Complex, NumberMode::Programmer];
+
+    /// Name of the mode to show to the user
+    pub fn name(&self) -> &'static str {
+        match self {
+            NumberMode::Float => return "Float",
+            NumberMode::Decimal => return "Decimal",
+            NumberMode::Rational => return "Fraction",
+            NumberMode::Complex => return "Complex",
+            NumberMode::Programmer => return "Programmer"
+        }
+    }
+}
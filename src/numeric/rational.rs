@@ -0,0 +1,241 @@
+// Exact fractions of arbitrarily large integers.
+// Unlike Decimal, numbers such as 1÷3 are stored exactly so
+// calculations like 1÷3×3 come out as 1
+use std::fmt;
+use std::str::FromStr;
+use bigdecimal::{BigDecimal, Signed, ToPrimitive, Zero, One};
+use bigdecimal::num_bigint::BigInt;
+use super::{Numeric, MAX_FACTORIAL};
+
+// Number of significant digits shown for fractions as decimals
+const DECIMAL_DIGITS: u64 = 20;
+// Largest integer power worked out exactly (larger powers of
+// fractions quickly become too large to be useful)
+const MAX_EXACT_POWER: u32 = 10000;
+// Most bits in the numerator or denominator of a power worked out
+// exactly, larger results are taken as outside the domain
+const MAX_EXACT_BITS: u64 = 100000;
+// Largest root (e.g 3 for cube roots) looked for exactly in powers
+const MAX_EXACT_ROOT: u32 = 64;
+
+/// Greatest common divisor of two integers (always non-negative)
+fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let mut a = a.abs();
+    let mut b = b.abs();
+    while !b.is_zero() {
+        let remainder = &a % &b;
+        a = b;
+        b = remainder;
+    }
+    return a;
+}
+
+/// The nth root of an integer, or None if it isn't a whole number
+fn exact_root(num: &BigInt, n: u32) -> Option<BigInt> {
+    if num.is_negative() && n.is_multiple_of(2) {
+        return None;
+    }
+    let root = num.nth_root(n);
+    if root.pow(n) != *num {
+        return None;
+    }
+    return Some(root);
+}
+
+// Kept in lowest terms with a positive denominator
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt
+}
+
+impl Rational {
+    /// Returns None if the denominator is zero
+    fn new(numer: BigInt, denom: BigInt) -> Option<Self> {
+        if denom.is_zero() {
+            return None;
+        }
+        let divisor = gcd(&numer, &denom);
+        let sign = match denom.is_negative() {
+            true => -BigInt::one(),
+            false => BigInt::one()
+        };
+        return Some(Rational {
+            numer: numer / &divisor * &sign,
+            denom: denom / &divisor * &sign
+        });
+    }
+
+    fn from_integer(num: BigInt) -> Self {
+        return Rational{numer: num, denom: BigInt::one()};
+    }
+
+    fn is_integer(&self) -> bool {
+        return self.denom.is_one();
+    }
+
+    /// Largest integer not above the number
+    fn floor_integer(&self) -> BigInt {
+        let quotient = &self.numer / &self.denom;
+        if self.numer.is_negative() && !self.is_integer() {
+            return quotient - 1;
+        }
+        return quotient;
+    }
+
+    /// Shown as a fraction with a slash e.g "-7/12"
+    pub fn fraction_string(&self) -> String {
+        if self.is_integer() {
+            return self.numer.to_string();
+        }
+        return format!("{}/{}", self.numer, self.denom);
+    }
+
+    /// Shown as a whole number and a proper fraction e.g "-1 1/12"
+    /// (for -13/12). Numbers below one are shown as just the fraction.
+    pub fn mixed_string(&self) -> String {
+        let whole = &self.numer / &self.denom;
+        if whole.is_zero() || self.is_integer() {
+            return self.fraction_string();
+        }
+        let remainder = (&self.numer % &self.denom).abs();
+        return format!("{} {}/{}", whole, remainder, self.denom);
+    }
+
+    /// Shown as a decimal, rounded to DECIMAL_DIGITS significant digits
+    /// if it can't be shown exactly e.g "0.58333333333333333333"
+    pub fn decimal_string(&self) -> String {
+        let decimal = BigDecimal::from(self.numer.clone()) / BigDecimal::from(self.denom.clone());
+        return decimal.with_prec(DECIMAL_DIGITS).normalized().to_plain_string();
+    }
+}
+
+impl fmt::Display for Rational {
+    // Shown as a division so the text can be read back as an expression
+    // by any number mode e.g "7÷12"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            return write!(f, "{}", self.numer);
+        }
+        return write!(f, "{}÷{}", self.numer, self.denom);
+    }
+}
+
+impl Numeric for Rational {
+    // Reads decimals (e.g "12.5") as well as fractions in the
+    // form they are displayed (e.g "7÷12")
+    fn parse_literal(text: &str) -> Option<Self> {
+        if let Some((numer, denom)) = text.split_once('÷') {
+            return Self::new(BigInt::from_str(numer).ok()?, BigInt::from_str(denom).ok()?);
+        }
+        let (int, exponent) = BigDecimal::from_str(text).ok()?.into_bigint_and_exponent();
+        // the value is int × 10^-exponent
+        let power_of_ten = BigInt::from(10).pow(exponent.unsigned_abs().try_into().ok()?);
+        if exponent < 0 {
+            return Some(Self::from_integer(int * power_of_ten));
+        }
+        return Self::new(int, power_of_ten);
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        if self.is_integer() {
+            return self.numer.to_f64();
+        }
+        return (BigDecimal::from(self.numer.clone()) / BigDecimal::from(self.denom.clone())).to_f64();
+    }
+    // Taken as the shortest decimal giving the same f64
+    // e.g 0.1 rather than 3602879701896397÷36028797018963968
+    fn from_f64(num: f64) -> Option<Self> {
+        if !num.is_finite() {
+            return None;
+        }
+        return Self::parse_literal(&num.to_string());
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        return Self::new(&self.numer * &other.denom + &other.numer * &self.denom, &self.denom * &other.denom);
+    }
+    fn subtract(&self, other: &Self) -> Option<Self> {
+        return Self::new(&self.numer * &other.denom - &other.numer * &self.denom, &self.denom * &other.denom);
+    }
+    fn multiply(&self, other: &Self) -> Option<Self> {
+        return Self::new(&self.numer * &other.numer, &self.denom * &other.denom);
+    }
+    fn divide(&self, other: &Self) -> Option<Self> {
+        return Self::new(&self.numer * &other.denom, &self.denom * &other.numer);
+    }
+    fn power(&self, exponent: &Self) -> Option<Self> {
+        // Integer powers (and roots of exact powers e.g (4÷9)^0.5)
+        // can be done exactly
+        let power = exponent.numer.abs().to_u32().filter(|power| *power <= MAX_EXACT_POWER);
+        let root = exponent.denom.to_u32().filter(|root| *root <= MAX_EXACT_ROOT);
+        if let (Some(power), Some(root)) = (power, root) {
+            if let (Some(numer), Some(denom)) = (exact_root(&self.numer, root), exact_root(&self.denom, root)) {
+                // Check the size of the result before working it out
+                if numer.bits().max(denom.bits()).saturating_mul(power as u64) > MAX_EXACT_BITS {
+                    return None;
+                }
+                let result = Self::new(numer.pow(power), denom.pow(power))?;
+                if exponent.numer.is_negative() {
+                    return Self::from_integer(BigInt::one()).divide(&result);
+                }
+                return Some(result);
+            }
+        }
+        // Otherwise fallback to floating point
+        let result = self.to_f64()?.powf(exponent.to_f64()?);
+        // results too small for f64 can't be given as zero
+        if !result.is_finite() || (result == 0.0 && !self.numer.is_zero()) {
+            return None;
+        }
+        return Self::from_f64(result);
+    }
+    // Exact when both parts are perfect squares e.g √(9÷16) = 3÷4
+    fn square_root(&self) -> Option<Self> {
+        if self.numer.is_negative() {
+            return None;
+        }
+        return self.power(&Rational{numer: BigInt::one(), denom: BigInt::from(2)});
+    }
+    fn negative(&self) -> Option<Self> {
+        return Some(Rational{numer: -&self.numer, denom: self.denom.clone()});
+    }
+
+    // Rounding functions can be done exactly
+    fn abs(&self) -> Option<Self> {
+        return Some(Rational{numer: self.numer.abs(), denom: self.denom.clone()});
+    }
+    fn floor(&self) -> Option<Self> {
+        return Some(Self::from_integer(self.floor_integer()));
+    }
+    fn ceil(&self) -> Option<Self> {
+        return Some(Self::from_integer(-self.negative()?.floor_integer()));
+    }
+    // Halfway cases are rounded away from zero
+    fn round(&self) -> Option<Self> {
+        let half = Rational{numer: BigInt::one(), denom: BigInt::from(2)};
+        let rounded = self.abs()?.add(&half)?.floor_integer();
+        if self.numer.is_negative() {
+            return Some(Self::from_integer(-rounded));
+        }
+        return Some(Self::from_integer(rounded));
+    }
+    // Factorials are done exactly with big integers
+    fn factorial(&self) -> Option<Self> {
+        if !self.is_integer() || self.numer.is_negative() {
+            return None;
+        }
+        let num = self.numer.to_u32().filter(|num| *num <= MAX_FACTORIAL)?;
+        let mut result = BigInt::one();
+        for i in 2..=num {
+            result *= i;
+        }
+        return Some(Self::from_integer(result));
+    }
+    fn modulo(&self, other: &Self) -> Option<Self> {
+        let quotient = self.divide(other)?;
+        // quotient rounded towards zero
+        let whole = &quotient.numer / &quotient.denom;
+        return self.subtract(&other.multiply(&Self::from_integer(whole))?);
+    }
+}
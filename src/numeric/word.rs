@@ -0,0 +1,211 @@
+// Fixed size integers as used by programmers (e.g 8-bit unsigned
+// integers aka bytes). Results outside of the word size wrap around
+// e.g 255+1 = 0 with unsigned bytes. Results which aren't whole
+// numbers are truncated towards zero e.g 7÷2 = 3.
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use super::{Numeric, MAX_FACTORIAL};
+
+/// Size of the integers used in programmer mode
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct WordSize {
+    // One of BITS
+    pub bits: u32,
+    // Whether negative numbers can be held (using two's complement)
+    pub signed: bool
+}
+impl WordSize {
+    pub const BITS: [u32; 4] = [8, 16, 32, 64];
+
+    pub fn new() -> Self {
+        return Self {
+            bits: 64,
+            signed: true
+        };
+    }
+
+    /// Name of the size to show to the user e.g "32-bit unsigned"
+    pub fn name(&self) -> String {
+        let signedness = match self.signed {
+            true => "signed",
+            false => "unsigned"
+        };
+        return format!("{}-bit {}", self.bits, signedness);
+    }
+
+    /// Wraps a value around into the range held by the word size
+    pub fn wrap(&self, value: i128) -> i128 {
+        let masked = value & ((1i128 << self.bits) - 1);
+        if self.signed && masked >= 1i128 << (self.bits - 1) {
+            return masked - (1i128 << self.bits);
+        }
+        return masked;
+    }
+
+    /// The bits of a value within the word size, read as an unsigned
+    /// number (e.g -1 is 255 for 8-bit words)
+    pub fn to_unsigned(&self, value: i128) -> u128 {
+        return (value & ((1i128 << self.bits) - 1)) as u128;
+    }
+}
+impl Default for WordSize {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Calls a function generic over Numeric with the Word type of the
+/// given word size e.g `with_word_type!(size, evaluate_infix_expr(expr, ctx))`.
+/// Sizes not in WordSize::BITS are taken as 64-bit.
+#[macro_export]
+macro_rules! with_word_type {
+    ($size:expr, $function:ident ( $($arg:expr),* )) => {
+        match ($size.bits, $size.signed) {
+            (8, false) => $function::<$crate::numeric::word::Word<8, false>>($($arg),*),
+            (8, true) => $function::<$crate::numeric::word::Word<8, true>>($($arg),*),
+            (16, false) => $function::<$crate::numeric::word::Word<16, false>>($($arg),*),
+            (16, true) => $function::<$crate::numeric::word::Word<16, true>>($($arg),*),
+            (32, false) => $function::<$crate::numeric::word::Word<32, false>>($($arg),*),
+            (32, true) => $function::<$crate::numeric::word::Word<32, true>>($($arg),*),
+            (_, false) => $function::<$crate::numeric::word::Word<64, false>>($($arg),*),
+            (_, true) => $function::<$crate::numeric::word::Word<64, true>>($($arg),*)
+        }
+    };
+}
+
+// An integer of the given number of bits. The value is always
+// kept within the range of the word size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word<const BITS: u32, const SIGNED: bool>(i128);
+
+impl<const BITS: u32, const SIGNED: bool> Word<BITS, SIGNED> {
+    const SIZE: WordSize = WordSize{bits: BITS, signed: SIGNED};
+
+    fn wrapped(value: i128) -> Self {
+        return Word(Self::SIZE.wrap(value));
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> fmt::Display for Word<BITS, SIGNED> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Numeric for Word<BITS, SIGNED> {
+    // Only whole numbers can be read
+    fn parse_literal(text: &str) -> Option<Self> {
+        return text.parse::<i128>().ok().map(Self::wrapped);
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        return Some(self.0 as f64);
+    }
+    fn from_f64(num: f64) -> Option<Self> {
+        if !num.is_finite() {
+            return None;
+        }
+        return Some(Self::wrapped(num.trunc() as i128));
+    }
+
+    fn add(&self, other: &Self) -> Option<Self> {
+        return Some(Self::wrapped(self.0 + other.0));
+    }
+    fn subtract(&self, other: &Self) -> Option<Self> {
+        return Some(Self::wrapped(self.0 - other.0));
+    }
+    fn multiply(&self, other: &Self) -> Option<Self> {
+        // only the lowest bits are kept, so overflowing i128 is harmless
+        return Some(Self::wrapped(self.0.wrapping_mul(other.0)));
+    }
+    fn divide(&self, other: &Self) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+        return Some(Self::wrapped(self.0 / other.0));
+    }
+    // Negative powers aren't whole numbers, so are left undefined
+    fn power(&self, exponent: &Self) -> Option<Self> {
+        if exponent.0 < 0 {
+            return None;
+        }
+        let mut remaining = exponent.0;
+        let mut result: i128 = 1;
+        let mut square = self.0;
+        while remaining > 0 {
+            if remaining % 2 == 1 {
+                result = Self::SIZE.wrap(result.wrapping_mul(square));
+            }
+            remaining /= 2;
+            square = Self::SIZE.wrap(square.wrapping_mul(square));
+        }
+        return Some(Word(result));
+    }
+    fn square_root(&self) -> Option<Self> {
+        return self.map_f64(f64::sqrt);
+    }
+    fn negative(&self) -> Option<Self> {
+        return Some(Self::wrapped(-self.0));
+    }
+
+    fn abs(&self) -> Option<Self> {
+        return Some(Self::wrapped(self.0.abs()));
+    }
+    // Whole numbers are already rounded
+    fn floor(&self) -> Option<Self> {
+        return Some(self.clone());
+    }
+    fn ceil(&self) -> Option<Self> {
+        return Some(self.clone());
+    }
+    fn round(&self) -> Option<Self> {
+        return Some(self.clone());
+    }
+    fn factorial(&self) -> Option<Self> {
+        if self.0 < 0 || self.0 > MAX_FACTORIAL as i128 {
+            return None;
+        }
+        let mut result: i128 = 1;
+        for i in 2..=self.0 {
+            result = Self::SIZE.wrap(result.wrapping_mul(i));
+        }
+        return Some(Word(result));
+    }
+
+    fn modulo(&self, other: &Self) -> Option<Self> {
+        if other.0 == 0 {
+            return None;
+        }
+        return Some(Self::wrapped(self.0 % other.0));
+    }
+    fn bit_and(&self, other: &Self) -> Option<Self> {
+        return Some(Self::wrapped(self.0 & other.0));
+    }
+    fn bit_or(&self, other: &Self) -> Option<Self> {
+        return Some(Self::wrapped(self.0 | other.0));
+    }
+    fn bit_xor(&self, other: &Self) -> Option<Self> {
+        return Some(Self::wrapped(self.0 ^ other.0));
+    }
+    fn bit_not(&self) -> Option<Self> {
+        return Some(Self::wrapped(!self.0));
+    }
+    // Shifting by the word size or more shifts out every bit
+    fn shift_left(&self, amount: &Self) -> Option<Self> {
+        if amount.0 < 0 {
+            return None;
+        }
+        if amount.0 >= BITS as i128 {
+            return Some(Word(0));
+        }
+        return Some(Self::wrapped(self.0 << amount.0));
+    }
+    // Signed numbers keep their sign when shifted right
+    fn shift_right(&self, amount: &Self) -> Option<Self> {
+        if amount.0 < 0 {
+            return None;
+        }
+        let amount = amount.0.min(BITS as i128);
+        return Some(Self::wrapped(self.0 >> amount));
+    }
+}
@@ -0,0 +1,279 @@
+/// Entry of calculations in Reverse Polish Notation, where numbers
+/// are entered onto a stack and operators apply directly to it
+
+use serde::{Deserialize, Serialize};
+use crate::expression_evaluate::{self, AngleMode, Context, EvalError, Operator};
+use crate::numeric::{Numeric, NumberMode, decimal::Decimal, rational::Rational, complex::Complex};
+
+/// Applies an operator to numbers given in text form (the last being the
+/// top of the stack) and gives the result in text form. Numbers entered
+/// in another number mode (e.g "7÷12") are read as expressions.
+fn apply_operator<N: Numeric>(op: &Operator, inputs: &[String], angle_mode: AngleMode) -> Result<String, EvalError> {
+    let mut nums: Vec<N> = Vec::new();
+    for input in inputs {
+        nums.push(expression_evaluate::text_to_number(input).ok_or(EvalError::MalformedNumber(0))?);
+    }
+    let result = match op {
+        Operator::OneInOperator(inside) => inside.apply(nums.pop().unwrap(), angle_mode),
+        Operator::TwoInOperator(inside) => {
+            let right_num = nums.pop().unwrap();
+            let left_num = nums.pop().unwrap();
+            inside.apply(left_num, right_num)
+        }
+        _ => return Err(EvalError::UnknownSymbol(0))
+    };
+    return result.map(|num| num.to_string()).ok_or(EvalError::DomainError(0));
+}
+
+/// Checks a value in text form is a plain number. Quantities are written
+/// with their units after a space (e.g "5000 m"), which the stack can't hold.
+fn check_plain_number(value: &str) -> Result<(), EvalError> {
+    if value.contains(' ') {
+        return Err(EvalError::DimensionMismatch(0));
+    }
+    return Ok(());
+}
+
+/// Number of inputs an operator takes off the stack.
+///
+/// Returns None for operators which can't apply to the stack.
+fn operator_inputs(op: &Operator) -> Option<usize> {
+    match op {
+        Operator::OneInOperator(_) => return Some(1),
+        Operator::TwoInOperator(_) => return Some(2),
+        _ => return None
+    }
+}
+
+// The stack of numbers (in text form) along with the number
+// currently being entered
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpnStack {
+    // Top of the stack is last
+    pub stack: Vec<String>,
+    pub entry: String
+}
+impl RpnStack {
+    pub fn new() -> Self {
+        return Self {
+            stack: Vec::new(),
+            entry: String::new()
+        };
+    }
+
+    /// Moves the number being entered onto the stack (if any).
+    /// Entries can be any expression e.g "2π" or a variable name,
+    /// but not quantities with units.
+    fn commit_entry(&mut self, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        if !self.entry.is_empty() {
+            let value = expression_evaluate::evaluate_with_mode(&self.entry, mode, ctx)?;
+            check_plain_number(&value)?;
+            self.stack.push(value);
+            self.entry.clear();
+        }
+        return Ok(());
+    }
+
+    /// ENTER: pushes the number being entered onto the stack, or
+    /// duplicates the top of the stack if nothing is being entered.
+    pub fn enter(&mut self, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        if self.entry.is_empty() {
+            let top = self.stack.last().cloned().ok_or(EvalError::MissingOperand(0))?;
+            self.stack.push(top);
+            return Ok(());
+        }
+        return self.commit_entry(mode, ctx);
+    }
+
+    /// Pushes a value onto the stack, entering any number being entered first.
+    pub fn push(&mut self, value: String, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        self.commit_entry(mode, ctx)?;
+        check_plain_number(&value)?;
+        self.stack.push(value);
+        return Ok(());
+    }
+
+    /// Gives the top of the stack, entering any number being entered first.
+    pub fn top(&mut self, mode: NumberMode, ctx: &Context) -> Result<String, EvalError> {
+        self.commit_entry(mode, ctx)?;
+        return self.stack.last().cloned().ok_or(EvalError::MissingOperand(0));
+    }
+
+    /// SWAP: swaps the top two numbers of the stack.
+    pub fn swap(&mut self, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        self.commit_entry(mode, ctx)?;
+        let len = self.stack.len();
+        if len < 2 {
+            return Err(EvalError::MissingOperand(0));
+        }
+        self.stack.swap(len - 1, len - 2);
+        return Ok(());
+    }
+
+    /// DROP: removes the top number of the stack.
+    pub fn drop(&mut self, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        self.commit_entry(mode, ctx)?;
+        self.stack.pop().ok_or(EvalError::MissingOperand(0))?;
+        return Ok(());
+    }
+
+    /// ROLL: rolls the stack down, moving the top number to the bottom.
+    pub fn roll(&mut self, mode: NumberMode, ctx: &Context) -> Result<(), EvalError> {
+        self.commit_entry(mode, ctx)?;
+        self.stack.rotate_right(1);
+        return Ok(());
+    }
+
+    /// Negates the number being entered, or the top of the stack if
+    /// nothing is being entered.
+    ///
+    /// Returns the calculation done on the stack (if any), as for apply.
+    pub fn change_sign(&mut self, mode: NumberMode, ctx: &Context) -> Result<Option<(String, String)>, EvalError> {
+        if self.entry.is_empty() {
+            let negative = Operator::OneInOperator(expression_evaluate::OneInOperator::Negative);
+            return self.apply(&negative, mode, ctx).map(Some);
+        }
+        match self.entry.strip_prefix('-') {
+            Some(positive) => self.entry = String::from(positive),
+            None => self.entry.insert(0, '-')
+        }
+        return Ok(None);
+    }
+
+    /// Applies an operator to the top of the stack, replacing its inputs
+    /// with the result.
+    ///
+    /// Returns the calculation done in postfix form (e.g "3 4 +") and its result.
+    pub fn apply(&mut self, op: &Operator, mode: NumberMode, ctx: &Context) -> Result<(String, String), EvalError> {
+        self.commit_entry(mode, ctx)?;
+        let n_inputs = operator_inputs(op).ok_or(EvalError::UnknownSymbol(0))?;
+        if self.stack.len() < n_inputs {
+            return Err(EvalError::MissingOperand(0));
+        }
+        let inputs_start = self.stack.len() - n_inputs;
+        let inputs = &self.stack[inputs_start..];
+        let result = match mode {
+            NumberMode::Float => apply_operator::<f64>(op, inputs, ctx.angle_mode)?,
+            NumberMode::Decimal => apply_operator::<Decimal>(op, inputs, ctx.angle_mode)?,
+            NumberMode::Rational => apply_operator::<Rational>(op, inputs, ctx.angle_mode)?,
+            NumberMode::Complex => apply_operator::<Complex>(op, inputs, ctx.angle_mode)?,
+            NumberMode::Programmer => basic_calculator::with_word_type!(ctx.word_size, apply_operator(op, inputs, ctx.angle_mode))?
+        };
+        let calculation = format!("{} {}", inputs.join(" "), op);
+        self.stack.truncate(inputs_start);
+        self.stack.push(result.clone());
+        return Ok((calculation, result));
+    }
+
+    /// Removes the last char being entered, or drops the top of the
+    /// stack if nothing is being entered.
+    pub fn backspace(&mut self) {
+        if self.entry.pop().is_none() {
+            self.stack.pop();
+        }
+    }
+
+    /// Clears the number being entered, or the whole stack if nothing
+    /// is being entered.
+    pub fn clear(&mut self) {
+        if self.entry.is_empty() {
+            self.stack.clear();
+        }
+        self.entry.clear();
+    }
+}
+impl Default for RpnStack {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gives a stack holding the given numbers (top last)
+    fn stack_of(nums: &[&str]) -> RpnStack {
+        let mut rpn = RpnStack::new();
+        rpn.stack = nums.iter().map(|num| String::from(*num)).collect();
+        return rpn;
+    }
+
+    #[test]
+    fn stack_operations() {
+        let ctx = Context::new();
+        let mode = NumberMode::Float;
+        let mut rpn = RpnStack::new();
+        rpn.entry = String::from("3");
+        rpn.enter(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["3"]);
+        assert!(rpn.entry.is_empty());
+        // entering nothing duplicates the top
+        rpn.enter(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["3", "3"]);
+
+        let mut rpn = stack_of(&["1", "2", "3"]);
+        rpn.swap(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["1", "3", "2"]);
+        rpn.roll(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["2", "1", "3"]);
+        rpn.drop(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["2", "1"]);
+        // numbers being entered go onto the stack first
+        rpn.entry = String::from("4");
+        rpn.swap(mode, &ctx).unwrap();
+        assert_eq!(rpn.stack, ["2", "4", "1"]);
+
+        let mut rpn = stack_of(&["1"]);
+        assert_eq!(rpn.swap(mode, &ctx).unwrap_err(), EvalError::MissingOperand(0));
+        let mut rpn = RpnStack::new();
+        assert_eq!(rpn.enter(mode, &ctx).unwrap_err(), EvalError::MissingOperand(0));
+        assert_eq!(rpn.drop(mode, &ctx).unwrap_err(), EvalError::MissingOperand(0));
+    }
+
+    #[test]
+    fn operators_and_editing() {
+        let ctx = Context::new();
+        let mode = NumberMode::Decimal;
+        let mut rpn = stack_of(&["3"]);
+        rpn.entry = String::from("4");
+        let add = expression_evaluate::operator_from_symbol("+").unwrap();
+        assert_eq!(rpn.apply(&add, mode, &ctx).unwrap(), (String::from("3 4 +"), String::from("7")));
+        assert_eq!(rpn.stack, ["7"]);
+        assert_eq!(rpn.apply(&add, mode, &ctx).unwrap_err(), EvalError::MissingOperand(0));
+
+        // change sign applies to the entry, or the top of the stack
+        rpn.entry = String::from("5");
+        assert_eq!(rpn.change_sign(mode, &ctx).unwrap(), None);
+        assert_eq!(rpn.entry, "-5");
+        rpn.change_sign(mode, &ctx).unwrap();
+        assert_eq!(rpn.entry, "5");
+        rpn.entry.clear();
+        assert!(rpn.change_sign(mode, &ctx).unwrap().is_some());
+        assert_eq!(rpn.stack, ["-7"]);
+
+        // backspace and clear work on the entry before the stack
+        rpn.entry = String::from("12");
+        rpn.backspace();
+        assert_eq!(rpn.entry, "1");
+        rpn.backspace();
+        rpn.backspace();
+        assert!(rpn.stack.is_empty());
+        let mut rpn = stack_of(&["1", "2"]);
+        rpn.entry = String::from("3");
+        rpn.clear();
+        assert_eq!((rpn.stack.len(), rpn.entry.as_str()), (2, ""));
+        rpn.clear();
+        assert!(rpn.stack.is_empty());
+
+        // numbers entered in other modes are read in the current mode
+        let mut rpn = stack_of(&["7÷12", "5÷12"]);
+        assert_eq!(rpn.apply(&add, NumberMode::Float, &ctx).unwrap().1, "1");
+
+        // the stack only holds plain numbers
+        rpn.entry = String::from("5 km");
+        assert_eq!(rpn.enter(mode, &ctx).unwrap_err(), EvalError::DimensionMismatch(0));
+        assert_eq!(rpn.stack, ["1"]);
+    }
+}
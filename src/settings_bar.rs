@@ -0,0 +1,241 @@
+/// The settings bar along the top of the calculator
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::numeric::{NumberMode, word::WordSize};
+use crate::expression_evaluate::NegationConvention;
+use crate::currency::CurrencyRates;
+use crate::number_format::{Notation, NumberFormat};
+
+// How calculations are entered
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EntryMode {
+    // Expressions written as usual then evaluated with "="
+    Algebraic,
+    // Numbers entered onto a stack which operators apply to directly
+    // (Reverse Polish Notation)
+    Rpn
+}
+impl EntryMode {
+    pub const ALL: [EntryMode; 2] = [EntryMode::Algebraic, EntryMode::Rpn];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EntryMode::Algebraic => return "Algebraic",
+            EntryMode::Rpn => return "RPN"
+        }
+    }
+}
+
+// How complex numbers are shown
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ComplexForm {
+    // Real and imaginary parts e.g 3+4i
+    Rectangular,
+    // Distance from zero and angle e.g 5∠53.13
+    Polar
+}
+impl ComplexForm {
+    pub const ALL: [ComplexForm; 2] = [ComplexForm::Rectangular, ComplexForm::Polar];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComplexForm::Rectangular => return "Rectangular",
+            ComplexForm::Polar => return "Polar"
+        }
+    }
+}
+
+// How fractions are shown
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FractionForm {
+    // e.g 13/12
+    Fraction,
+    // Whole number and proper fraction e.g 1 1/12
+    Mixed,
+    // e.g 1.0833333333333333333
+    Decimal
+}
+impl FractionForm {
+    pub const ALL: [FractionForm; 3] = [FractionForm::Fraction, FractionForm::Mixed, FractionForm::Decimal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FractionForm::Fraction => return "Fraction",
+            FractionForm::Mixed => return "Mixed number",
+            FractionForm::Decimal => return "Decimal"
+        }
+    }
+}
+
+// Base answers are shown in when using programmer mode
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Base {
+    Decimal,
+    Hexadecimal,
+    Octal,
+    Binary
+}
+impl Base {
+    pub const ALL: [Base; 4] = [Base::Decimal, Base::Hexadecimal, Base::Octal, Base::Binary];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Base::Decimal => return "DEC",
+            Base::Hexadecimal => return "HEX",
+            Base::Octal => return "OCT",
+            Base::Binary => return "BIN"
+        }
+    }
+}
+
+// Settings which change how the calculator behaves.
+// Missing fields in saved settings are filled with defaults.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub number_mode: NumberMode,
+    pub entry_mode: EntryMode,
+    // How answers are shown when using complex numbers
+    pub complex_form: ComplexForm,
+    // How answers are shown when using fractions
+    pub fraction_form: FractionForm,
+    // Base answers are shown in when using programmer mode
+    pub display_base: Base,
+    // Notation and digits answers are shown with
+    pub number_format: NumberFormat,
+    // Maximum number of past calculations kept in the history
+    pub max_history: usize,
+    // Whether the working of the last evaluation is shown
+    pub show_working: bool
+}
+impl Settings {
+    pub fn new() -> Self {
+        return Self {
+            number_mode: NumberMode::Decimal,
+            entry_mode: EntryMode::Algebraic,
+            complex_form: ComplexForm::Rectangular,
+            fraction_form: FractionForm::Fraction,
+            display_base: Base::Decimal,
+            number_format: NumberFormat::new(),
+            max_history: 100,
+            show_working: false
+        };
+    }
+}
+impl Default for Settings {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+/// Places a row of controls for each setting onto the ui given.
+/// The negation convention and word size are kept with the evaluation
+/// context rather than the settings. The date of the exchange rates
+/// is shown at the end, or `rates_error` if they couldn't be read.
+pub fn show_settings_bar(ui: &mut egui::Ui, settings: &mut Settings, negation: &mut NegationConvention, word_size: &mut WordSize, currencies: &CurrencyRates, rates_error: &Option<String>) {
+    ui.horizontal(|ui| {
+        // Number type used for calculations
+        egui::ComboBox::from_label("Numbers")
+            .selected_text(settings.number_mode.name())
+            .show_ui(ui, |ui| {
+                for mode in NumberMode::ALL {
+                    ui.selectable_value(&mut settings.number_mode, mode, mode.name());
+                }
+            });
+        // Form complex answers are shown in
+        if settings.number_mode == NumberMode::Complex {
+            egui::ComboBox::from_label("Form")
+                .selected_text(settings.complex_form.name())
+                .show_ui(ui, |ui| {
+                    for form in ComplexForm::ALL {
+                        ui.selectable_value(&mut settings.complex_form, form, form.name());
+                    }
+                });
+        }
+        // Form fraction answers are shown in
+        if settings.number_mode == NumberMode::Rational {
+            egui::ComboBox::from_label("Show as")
+                .selected_text(settings.fraction_form.name())
+                .show_ui(ui, |ui| {
+                    for form in FractionForm::ALL {
+                        ui.selectable_value(&mut settings.fraction_form, form, form.name());
+                    }
+                });
+        }
+        // Size of integers and the base answers are shown in
+        if settings.number_mode == NumberMode::Programmer {
+            egui::ComboBox::from_label("Word")
+                .selected_text(word_size.name())
+                .show_ui(ui, |ui| {
+                    for bits in WordSize::BITS {
+                        for signed in [true, false] {
+                            let size = WordSize{bits, signed};
+                            ui.selectable_value(word_size, size, size.name());
+                        }
+                    }
+                });
+            egui::ComboBox::from_label("Base")
+                .selected_text(settings.display_base.name())
+                .show_ui(ui, |ui| {
+                    for base in Base::ALL {
+                        ui.selectable_value(&mut settings.display_base, base, base.name());
+                    }
+                });
+        }
+        // How answers are written, programmer integers are only shown in a base
+        if settings.number_mode != NumberMode::Programmer {
+            ui.separator();
+            let format = &mut settings.number_format;
+            egui::ComboBox::from_label("Format")
+                .selected_text(format.notation.name())
+                .show_ui(ui, |ui| {
+                    for notation in Notation::ALL {
+                        ui.selectable_value(&mut format.notation, notation, notation.name());
+                    }
+                });
+            if format.notation.uses_digits() {
+                let label = match format.notation {
+                    Notation::Fixed => "Decimals",
+                    _ => "Figures"
+                };
+                ui.add(egui::DragValue::new(&mut format.digits).clamp_range(0..=50)).on_hover_text(label);
+                ui.label(label);
+            }
+            ui.label("Max digits");
+            ui.add(egui::DragValue::new(&mut format.max_digits).clamp_range(1..=50));
+            ui.checkbox(&mut format.thousands_separators, "1,000");
+        }
+        ui.separator();
+        // How calculations are entered
+        egui::ComboBox::from_label("Entry")
+            .selected_text(settings.entry_mode.name())
+            .show_ui(ui, |ui| {
+                for mode in EntryMode::ALL {
+                    ui.selectable_value(&mut settings.entry_mode, mode, mode.name());
+                }
+            });
+        ui.separator();
+        // Whether negatives apply before or after powers
+        egui::ComboBox::from_label("Negatives")
+            .selected_text(negation.name())
+            .show_ui(ui, |ui| {
+                for convention in NegationConvention::ALL {
+                    ui.selectable_value(negation, convention, convention.name());
+                }
+            });
+        ui.separator();
+        // Length of the history
+        ui.label("History length");
+        ui.add(egui::DragValue::new(&mut settings.max_history).clamp_range(1..=10000));
+        ui.separator();
+        ui.checkbox(&mut settings.show_working, "Show working");
+        ui.separator();
+        // When the exchange rates were last updated
+        match (rates_error, &currencies.updated) {
+            (Some(error), _) => ui.label("No exchange rates").on_hover_text(error),
+            (None, Some(updated)) => ui.label(format!("Rates updated {}", updated)),
+            (None, None) => ui.label("Rates undated")
+        };
+    });
+}
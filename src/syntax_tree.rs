@@ -0,0 +1,613 @@
+/// Abstract syntax trees of infix expressions.
+/// Unlike Postfix, a tree keeps the structure of the expression and
+/// where each part was written, for tools such as highlighting.
+/// Units are checked as in the evaluator, including conversions.
+
+use std::fmt;
+use crate::numeric::Numeric;
+use crate::units::{Dimension, Quantity};
+use crate::expression_evaluate::{
+    parse_tokens, call_user_function, one_in_dimension, two_in_dimension, convert_quantity,
+    Context, EvalError, NegationConvention,
+    Token, PositionedToken, Operator, OneInOperator, TwoInOperator, Bracket
+};
+
+/// Range of chars in the infix expression that a node was read from.
+/// `start` is inclusive and `end` is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+/// The kinds of node in a tree
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr<N: Numeric> {
+    // Number written with digits
+    Number(N),
+    // Constant, Ans, variable or unit, with its value and
+    // dimension when parsed
+    Name(String, N, Dimension),
+    // Operator with a single input (including functions e.g sin(x))
+    Unary {
+        op: OneInOperator,
+        op_span: Span,
+        operand: Box<Node<N>>
+    },
+    // Operator with two inputs (including log(base, number)).
+    // Implied multiplication (e.g 2π) has an empty op_span
+    // at the start of the right input.
+    Binary {
+        op: TwoInOperator,
+        op_span: Span,
+        left: Box<Node<N>>,
+        right: Box<Node<N>>
+    },
+    // Percentage of the operand, taken of the left input instead
+    // when the right input of an add or subtract (e.g 200+10%)
+    Percent {
+        op_span: Span,
+        operand: Box<Node<N>>
+    },
+    // Call to a user defined function
+    Call {
+        name: String,
+        name_span: Span,
+        inputs: Vec<Node<N>>
+    },
+    // Conversion into the units of the target (e.g 5 km to mi),
+    // only at the top of a tree
+    Convert {
+        op_span: Span,
+        value: Box<Node<N>>,
+        target: Box<Node<N>>
+    }
+}
+
+/// A node of a tree along with the span it was read from.
+/// Spans of bracketed expressions include the brackets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<N: Numeric> {
+    pub expr: Expr<N>,
+    pub span: Span
+}
+
+// Precedence of conversions, which take in everything around them
+const CONVERT_PRIORITY: i32 = 0;
+// Precedence of negatives (between multiplication and powers)
+const NEGATIVE_PRIORITY: i32 = 7;
+// Precedence of powers
+const POWER_PRIORITY: i32 = 8;
+// Precedence of other prefix operators (e.g √x)
+const PREFIX_PRIORITY: i32 = 10;
+// Precedence of factorials and percents
+const POSTFIX_PRIORITY: i32 = 12;
+// Precedence of numbers, names and function calls
+const ATOM_PRIORITY: i32 = 14;
+
+/// Precedence of infix operators (BEDMAS), matching the evaluator's.
+///
+/// Returns None for operators written as functions.
+fn infix_priority(op: &TwoInOperator) -> Option<i32> {
+    match op {
+        TwoInOperator::Or => return Some(1),
+        TwoInOperator::Xor => return Some(2),
+        TwoInOperator::And => return Some(3),
+        TwoInOperator::ShiftLeft | TwoInOperator::ShiftRight => return Some(4),
+        TwoInOperator::Add | TwoInOperator::Subtract => return Some(5),
+        TwoInOperator::Multiply | TwoInOperator::Divide | TwoInOperator::Modulo => return Some(6),
+        TwoInOperator::Power => return Some(POWER_PRIORITY),
+        TwoInOperator::Log => return None
+    }
+}
+
+impl<N: Numeric> Node<N> {
+    /// How tightly the node holds together when printed,
+    /// used to decide where brackets are needed.
+    fn priority(&self) -> i32 {
+        match &self.expr {
+            Expr::Number(num) => {
+                // some number types are written as sums or divisions
+                // e.g "3+4i" or "1÷2", so hold together as loosely
+                let text = num.to_string();
+                let unsigned = text.strip_prefix('-').unwrap_or(&text);
+                if unsigned.contains(['+', '-']) {
+                    return infix_priority(&TwoInOperator::Add).unwrap();
+                }
+                if unsigned.contains('÷') || unsigned.ends_with('i') {
+                    return infix_priority(&TwoInOperator::Multiply).unwrap();
+                }
+                // negative numbers can only come from trees built by hand
+                if text.starts_with('-') {
+                    return NEGATIVE_PRIORITY;
+                }
+                return ATOM_PRIORITY;
+            }
+            Expr::Name(..) | Expr::Call{..} => return ATOM_PRIORITY,
+            Expr::Unary{op: OneInOperator::Factorial, ..} | Expr::Percent{..} => return POSTFIX_PRIORITY,
+            Expr::Unary{op: OneInOperator::Negative, ..} => return NEGATIVE_PRIORITY,
+            Expr::Unary{op: OneInOperator::SquareRoot | OneInOperator::Not, ..} => return PREFIX_PRIORITY,
+            Expr::Unary{..} => return ATOM_PRIORITY,
+            Expr::Binary{op, ..} => return infix_priority(op).unwrap_or(ATOM_PRIORITY),
+            Expr::Convert{..} => return CONVERT_PRIORITY
+        }
+    }
+
+    /// Writes the node, wrapped in brackets if it holds together
+    /// less tightly than `min_priority`.
+    fn fmt_within(&self, f: &mut fmt::Formatter, min_priority: i32) -> fmt::Result {
+        if self.priority() < min_priority {
+            return write!(f, "({})", self);
+        }
+        return write!(f, "{}", self);
+    }
+}
+
+impl<N: Numeric> fmt::Display for Node<N> {
+    // Prints the tree as canonical infix: operators written with the
+    // calculator's own symbols, implied multiplications written out,
+    // function inputs always bracketed and no unneeded brackets besides
+    // those around negatives next to powers (so the text reads the same
+    // under either NegationConvention).
+    // Parsing the printed text gives back the same tree (besides spans).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.expr {
+            Expr::Number(num) => return write!(f, "{}", num),
+            Expr::Name(name, ..) => return write!(f, "{}", name),
+            Expr::Unary{op, operand, ..} => {
+                match op {
+                    OneInOperator::Negative => {
+                        write!(f, "-")?;
+                        return operand.fmt_within(f, POWER_PRIORITY + 1);
+                    }
+                    OneInOperator::SquareRoot | OneInOperator::Not => {
+                        write!(f, "{}", op)?;
+                        return operand.fmt_within(f, PREFIX_PRIORITY);
+                    }
+                    OneInOperator::Factorial => {
+                        operand.fmt_within(f, POSTFIX_PRIORITY)?;
+                        return write!(f, "!");
+                    }
+                    _ => return write!(f, "{}({})", op, operand)
+                }
+            }
+            Expr::Percent{operand, ..} => {
+                operand.fmt_within(f, POSTFIX_PRIORITY)?;
+                return write!(f, "%");
+            }
+            Expr::Binary{op, left, right, ..} => {
+                match infix_priority(op) {
+                    None => return write!(f, "{}({},{})", op, left, right),
+                    Some(priority) => {
+                        // an input of the same precedence needs brackets on
+                        // the side the operator doesn't associate from
+                        // (negatives before a power are always bracketed
+                        // as they hold together less tightly than powers)
+                        let (left_min, right_min) = match op.is_right_associative() {
+                            true => (priority + 1, priority),
+                            false => (priority, priority + 1)
+                        };
+                        left.fmt_within(f, left_min)?;
+                        write!(f, "{}", op)?;
+                        return right.fmt_within(f, right_min);
+                    }
+                }
+            }
+            Expr::Call{name, inputs, ..} => {
+                let inputs: Vec<String> = inputs.iter().map(|input| input.to_string()).collect();
+                return write!(f, "{}({})", name, inputs.join(","));
+            }
+            Expr::Convert{value, target, ..} => return write!(f, "{} to {}", value, target)
+        }
+    }
+}
+
+// Reads Tokens into a tree by precedence climbing
+struct Parser<N: Numeric> {
+    tokens: Vec<PositionedToken<N>>,
+    // char offset where each token ends
+    ends: Vec<usize>,
+    // the infix expression's chars, for reading the names of values
+    chars: Vec<char>,
+    // index of the next token to read
+    position: usize,
+    negation: NegationConvention
+}
+impl<N: Numeric> Parser<N> {
+    fn new(tokens: Vec<PositionedToken<N>>, expr: &str, negation: NegationConvention) -> Self {
+        let chars: Vec<char> = expr.chars().collect();
+        // each token ends where the next starts, less any spaces between
+        let ends: Vec<usize> = tokens.iter().skip(1)
+            .map(|token| token.offset)
+            .chain(std::iter::once(chars.len()))
+            .map(|end| end - chars[..end].iter().rev().take_while(|ch| ch.is_whitespace()).count())
+            .collect();
+        return Self {
+            tokens,
+            ends,
+            chars,
+            position: 0,
+            negation
+        };
+    }
+
+    fn peek(&self) -> Option<&Token<N>> {
+        return self.tokens.get(self.position).map(|token| &token.token);
+    }
+
+    /// Span of the next token (empty at the end of the expression)
+    fn peek_span(&self) -> Span {
+        match self.tokens.get(self.position) {
+            Some(token) => return Span{start: token.offset, end: self.ends[self.position]},
+            None => return Span{start: self.chars.len(), end: self.chars.len()}
+        }
+    }
+
+    /// Reads an expression whose infix operators are all at least
+    /// `min_priority`.
+    /// `needed_by` is the offset of what needs the expression, where
+    /// a missing operand is reported.
+    fn parse_expr(&mut self, min_priority: i32, needed_by: usize) -> Result<Node<N>, EvalError> {
+        let mut left = self.parse_prefix(needed_by)?;
+        loop {
+            let op_span = self.peek_span();
+            let (op, priority) = match self.peek() {
+                Some(Token::Operator(Operator::TwoInOperator(op))) if infix_priority(op).is_some() => {
+                    (op.clone(), infix_priority(op).unwrap())
+                }
+                _ => break
+            };
+            if priority < min_priority {
+                break;
+            }
+            self.position += 1;
+            // a right associative operator takes in any more of itself on the right
+            let right_min = match op.is_right_associative() {
+                true => priority,
+                false => priority + 1
+            };
+            let right = self.parse_expr(right_min, op_span.start)?;
+            let span = Span{start: left.span.start, end: right.span.end};
+            left = Node{expr: Expr::Binary{op, op_span, left: Box::new(left), right: Box::new(right)}, span};
+        }
+        return Ok(left);
+    }
+
+    /// Reads a value, bracketed expression, function call or prefix
+    /// operator (along with any factorials or percents after it).
+    fn parse_prefix(&mut self, needed_by: usize) -> Result<Node<N>, EvalError> {
+        let span = self.peek_span();
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token.token.clone(),
+            None => return Err(EvalError::MissingOperand(needed_by))
+        };
+        let mut node = match token {
+            Token::Number(num) => {
+                self.position += 1;
+                let text: String = self.chars[span.start..span.end].iter().collect();
+                if text.starts_with(|ch: char| ch.is_ascii_digit() || ch == '.') {
+                    Node{expr: Expr::Number(num), span}
+                } else {
+                    Node{expr: Expr::Name(text, num, Dimension::NONE), span}
+                }
+            }
+            // units (and temperatures e.g "20°C") are kept as written
+            Token::Quantity(num, dimension) => {
+                self.position += 1;
+                let text: String = self.chars[span.start..span.end].iter().collect();
+                Node{expr: Expr::Name(text, num, dimension), span}
+            }
+            Token::Operator(Operator::Bracket(Bracket::Open)) => {
+                self.position += 1;
+                let inner = self.parse_expr(0, span.start)?;
+                let close_span = self.expect_close(span.start)?;
+                Node{expr: inner.expr, span: Span{start: span.start, end: close_span.end}}
+            }
+            Token::Operator(Operator::OneInOperator(OneInOperator::Negative | OneInOperator::SquareRoot | OneInOperator::Not)) => {
+                self.position += 1;
+                let op = match token {
+                    Token::Operator(Operator::OneInOperator(op)) => op,
+                    _ => unreachable!()
+                };
+                // negatives take in powers after them, unless following
+                // the calculator convention
+                let operand = match op {
+                    OneInOperator::Negative if self.negation == NegationConvention::Mathematical => self.parse_expr(POWER_PRIORITY, span.start)?,
+                    _ => self.parse_prefix(span.start)?
+                };
+                let node_span = Span{start: span.start, end: operand.span.end};
+                Node{expr: Expr::Unary{op, op_span: span, operand: Box::new(operand)}, span: node_span}
+            }
+            Token::Operator(function @ (Operator::OneInOperator(_) | Operator::TwoInOperator(TwoInOperator::Log) | Operator::UserFunction(..))) => {
+                self.position += 1;
+                self.parse_function(function, span)?
+            }
+            Token::Operator(Operator::Bracket(Bracket::Close)) => return Err(EvalError::UnbalancedBracket(span.start)),
+            Token::Operator(Operator::Separator | Operator::Convert) => return Err(EvalError::UnknownSymbol(span.start)),
+            Token::Operator(_) => return Err(EvalError::MissingOperand(span.start))
+        };
+
+        // factorials and percents come after what they apply to
+        loop {
+            let op_span = self.peek_span();
+            let node_span = Span{start: node.span.start, end: op_span.end};
+            let operand = Box::new(node.clone());
+            let expr = match self.peek() {
+                Some(Token::Operator(Operator::OneInOperator(OneInOperator::Factorial))) => Expr::Unary{op: OneInOperator::Factorial, op_span, operand},
+                Some(Token::Operator(Operator::Percent)) => Expr::Percent{op_span, operand},
+                _ => break
+            };
+            self.position += 1;
+            node = Node{expr, span: node_span};
+        }
+        return Ok(node);
+    }
+
+    /// Reads the inputs of a function whose name has just been read.
+    fn parse_function(&mut self, function: Operator, name_span: Span) -> Result<Node<N>, EvalError> {
+        let arity = function.function_arity().unwrap_or(1);
+        let mut inputs: Vec<Node<N>> = Vec::new();
+        let end: usize;
+        if let Some(Token::Operator(Operator::Bracket(Bracket::Open))) = self.peek() {
+            // bracketed inputs separated by commas
+            let open_offset = self.peek_span().start;
+            self.position += 1;
+            loop {
+                inputs.push(self.parse_expr(0, open_offset)?);
+                match self.peek() {
+                    Some(Token::Operator(Operator::Separator)) => self.position += 1,
+                    _ => break
+                }
+            }
+            end = self.expect_close(open_offset)?.end;
+            if inputs.len() != arity {
+                return Err(EvalError::ArgumentCount(name_span.start));
+            }
+        } else if arity == 1 {
+            // single input functions can be written without brackets e.g sin30
+            inputs.push(self.parse_prefix(name_span.start)?);
+            end = inputs[0].span.end;
+        } else {
+            return Err(EvalError::ArgumentCount(name_span.start));
+        }
+
+        let span = Span{start: name_span.start, end};
+        let expr = match function {
+            Operator::UserFunction(name, _) => Expr::Call{name, name_span, inputs},
+            Operator::TwoInOperator(op) => {
+                let right = inputs.pop().unwrap();
+                let left = inputs.pop().unwrap();
+                Expr::Binary{op, op_span: name_span, left: Box::new(left), right: Box::new(right)}
+            }
+            Operator::OneInOperator(op) => Expr::Unary{op, op_span: name_span, operand: Box::new(inputs.pop().unwrap())},
+            _ => unreachable!()
+        };
+        return Ok(Node{expr, span});
+    }
+
+    /// Reads the close bracket matching the open bracket at `open_offset`.
+    fn expect_close(&mut self, open_offset: usize) -> Result<Span, EvalError> {
+        let span = self.peek_span();
+        match self.peek() {
+            Some(Token::Operator(Operator::Bracket(Bracket::Close))) => {
+                self.position += 1;
+                return Ok(span);
+            }
+            // conversions can't be within brackets
+            Some(Token::Operator(Operator::Separator | Operator::Convert)) => return Err(EvalError::UnknownSymbol(span.start)),
+            _ => return Err(EvalError::UnbalancedBracket(open_offset))
+        }
+    }
+}
+
+/// Parses an infix string expression into a tree.
+/// The expression can end in a conversion to other units (e.g "5 km to mi").
+pub fn parse<N: Numeric>(expr: &str, ctx: &Context) -> Result<Node<N>, EvalError> {
+    let mut parser = Parser::new(parse_tokens(expr, ctx)?, expr, ctx.negation);
+    let mut tree = parser.parse_expr(0, 0)?;
+    if let Some(Token::Operator(Operator::Convert)) = parser.peek() {
+        let op_span = parser.peek_span();
+        parser.position += 1;
+        let target = parser.parse_expr(0, op_span.start)?;
+        let span = Span{start: tree.span.start, end: target.span.end};
+        tree = Node{expr: Expr::Convert{op_span, value: Box::new(tree), target: Box::new(target)}, span};
+    }
+    // anything left over can't follow on from the expression
+    match parser.peek() {
+        None => return Ok(tree),
+        Some(Token::Operator(Operator::Separator)) => return Err(EvalError::UnknownSymbol(parser.peek_span().start)),
+        Some(Token::Operator(Operator::Bracket(Bracket::Close))) => return Err(EvalError::UnbalancedBracket(parser.peek_span().start)),
+        Some(_) => return Err(EvalError::MissingOperator(parser.peek_span().start))
+    }
+}
+
+/// Evaluates the answer of a tree (in base units, or the units
+/// converted to).
+/// Names are given the values they had when parsed.
+pub fn evaluate<N: Numeric>(node: &Node<N>, ctx: &Context) -> Result<N, EvalError> {
+    return evaluate_quantity(node, ctx).map(|answer| answer.value);
+}
+
+/// Evaluates the answer of a tree along with its dimension, checking
+/// units as the evaluator does.
+pub fn evaluate_quantity<N: Numeric>(node: &Node<N>, ctx: &Context) -> Result<Quantity<N>, EvalError> {
+    match &node.expr {
+        Expr::Number(num) => return Ok(Quantity::number(num.clone())),
+        Expr::Name(_, num, dimension) => return Ok(Quantity{value: num.clone(), dimension: *dimension, unit: None}),
+        Expr::Unary{op, op_span, operand} => {
+            let input = evaluate_quantity(operand, ctx)?;
+            let dimension = one_in_dimension(op, input.dimension).ok_or(EvalError::DimensionMismatch(op_span.start))?;
+            let value = op.apply(input.value, ctx.angle_mode).ok_or(EvalError::DomainError(op_span.start))?;
+            return Ok(Quantity{value, dimension, unit: None});
+        }
+        Expr::Binary{op, op_span, left, right} => {
+            let left = evaluate_quantity(left, ctx)?;
+            let mut right_input = evaluate_quantity(right, ctx)?;
+            // adding a percentage adds that percentage of the left input
+            if matches!(op, TwoInOperator::Add | TwoInOperator::Subtract) && is_percentage(right) {
+                right_input.value = left.value.multiply(&right_input.value).ok_or(EvalError::DomainError(op_span.start))?;
+                right_input.dimension = left.dimension.multiply(&right_input.dimension);
+            }
+            let dimension = two_in_dimension(op, left.dimension, right_input.dimension, &right_input.value)
+                .ok_or(EvalError::DimensionMismatch(op_span.start))?;
+            let value = op.apply(left.value, right_input.value).ok_or(EvalError::DomainError(op_span.start))?;
+            return Ok(Quantity{value, dimension, unit: None});
+        }
+        Expr::Percent{op_span, operand} => {
+            let input = evaluate_quantity(operand, ctx)?;
+            let hundred = N::parse_literal("100").ok_or(EvalError::MalformedNumber(op_span.start))?;
+            let value = input.value.divide(&hundred).ok_or(EvalError::DomainError(op_span.start))?;
+            return Ok(Quantity{value, dimension: input.dimension, unit: None});
+        }
+        Expr::Call{name, name_span, inputs} => {
+            let inputs: Vec<Quantity<N>> = inputs.iter().map(|input| evaluate_quantity(input, ctx)).collect::<Result<_, _>>()?;
+            return call_user_function(name, inputs, ctx, name_span.start);
+        }
+        Expr::Convert{op_span, value, target} => {
+            let answer = evaluate_quantity(value, ctx)?;
+            let target_quantity = evaluate_quantity(target, ctx)?;
+            return convert_quantity(answer, target_quantity, target.to_string(), op_span.start);
+        }
+    }
+}
+
+/// Whether a node is a percentage (possibly negated e.g -10%),
+/// matching how the evaluator treats percents.
+fn is_percentage<N: Numeric>(node: &Node<N>) -> bool {
+    match &node.expr {
+        Expr::Percent{..} => return true,
+        Expr::Unary{op: OneInOperator::Negative, operand, ..} => return is_percentage(operand),
+        _ => return false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression_evaluate::evaluate_infix_expr;
+    use crate::numeric::rational::Rational;
+
+    #[test]
+    fn round_trip_printing() {
+        let ctx = Context::new();
+        let expr_and_canonical = [
+            ("1+2×3", "1+2×3"),
+            ("((1+2))×3", "(1+2)×3"),
+            ("1-(2-3)", "1-(2-3)"),
+            ("(1-2)-3", "1-2-3"),
+            ("2^(3^2)", "2^3^2"),
+            ("(2^3)^2", "(2^3)^2"),
+            ("-2^2", "-(2^2)"),
+            ("(-2)^2", "(-2)^2"),
+            ("2π", "2×π"),
+            ("2(3+4)", "2×(3+4)"),
+            ("sin30+log(2,8)", "sin(30)+log(2,8)"),
+            ("-(2+3)!", "-(2+3)!"),
+            ("(1+2)!", "(1+2)!"),
+            ("(-3)×2", "-3×2"),
+            ("(5)-3", "5-3"),
+            ("√(4)×Ans", "√4×Ans"),
+            ("√(1+3)", "√(1+3)"),
+            ("--2", "-(-2)"),
+            ("1.50+e", "1.5+e"),
+            ("200+(10%)", "200+10%"),
+            ("(2+3)!%", "(2+3)!%")
+        ];
+        for item in expr_and_canonical {
+            let tree = parse::<f64>(item.0, &ctx).unwrap();
+            let printed = tree.to_string();
+            assert_eq!(printed, item.1);
+            // printing then parsing gives the same answer and text
+            let reparsed = parse::<f64>(&printed, &ctx).unwrap();
+            assert_eq!(reparsed.to_string(), printed);
+            assert_eq!(evaluate(&reparsed, &ctx).unwrap(), evaluate(&tree, &ctx).unwrap());
+        }
+        // fractions are written as divisions, so are bracketed like them
+        let fraction_and_canonical = [
+            ("2^0.5", "2^(1÷2)"),
+            ("0.5^2", "(1÷2)^2"),
+            ("3÷0.25", "3÷(1÷4)"),
+            ("0.5×3", "1÷2×3")
+        ];
+        for item in fraction_and_canonical {
+            let tree = parse::<Rational>(item.0, &ctx).unwrap();
+            let printed = tree.to_string();
+            assert_eq!(printed, item.1);
+            let reparsed = parse::<Rational>(&printed, &ctx).unwrap();
+            assert_eq!(reparsed.to_string(), printed);
+            assert_eq!(evaluate(&reparsed, &ctx).unwrap(), evaluate(&tree, &ctx).unwrap());
+        }
+    }
+
+    #[test]
+    fn spans_and_errors() {
+        let ctx = Context::new();
+        let tree = parse::<f64>("2×(3+4)!", &ctx).unwrap();
+        assert_eq!(tree.span, Span{start: 0, end: 8});
+        match &tree.expr {
+            Expr::Binary{op: TwoInOperator::Multiply, op_span, right, ..} => {
+                assert_eq!(*op_span, Span{start: 1, end: 2});
+                assert_eq!(right.span, Span{start: 2, end: 8});
+            }
+            _ => panic!("expected a multiplication")
+        }
+        assert_eq!(evaluate(&tree, &ctx).unwrap(), 10080.0);
+
+        let expr_and_err = [
+            ("", EvalError::MissingOperand(0)),
+            ("1+", EvalError::MissingOperand(1)),
+            ("(1+2", EvalError::UnbalancedBracket(0)),
+            ("1+2)", EvalError::UnbalancedBracket(3)),
+            ("1,2", EvalError::UnknownSymbol(1)),
+            ("log(8)", EvalError::ArgumentCount(0)),
+            ("1÷0", EvalError::DomainError(1))
+        ];
+        for item in expr_and_err {
+            let result = parse::<f64>(item.0, &ctx).and_then(|tree| evaluate(&tree, &ctx));
+            assert_eq!(result.unwrap_err(), item.1);
+        }
+    }
+
+    #[test]
+    fn units() {
+        let ctx = Context::new();
+        let tree = parse::<f64>("5 km+2 m", &ctx).unwrap();
+        assert_eq!(tree.to_string(), "5×km+2×m");
+        let answer = evaluate_quantity(&tree, &ctx).unwrap();
+        assert_eq!((answer.value, answer.dimension.to_string()), (5002.0, String::from("m")));
+        // conversions only come at the top of a tree
+        let tree = parse::<f64>("6 km to m", &ctx).unwrap();
+        assert_eq!(tree.to_string(), "6×km to m");
+        assert_eq!(parse::<f64>(&tree.to_string(), &ctx).unwrap().to_string(), "6×km to m");
+        assert_eq!(evaluate_quantity(&tree, &ctx).unwrap().to_string(), "6000 m");
+        let tree = parse::<f64>("0°C to K", &ctx).unwrap();
+        assert_eq!(evaluate_quantity(&tree, &ctx).unwrap().to_string(), "273.15 K");
+        // units are checked as in the evaluator
+        let expr_and_err = [
+            ("5 km+2 kg", EvalError::DimensionMismatch(4)),
+            ("sin(2m)", EvalError::DimensionMismatch(0)),
+            ("5 km to s", EvalError::DimensionMismatch(5)),
+            ("5 km to", EvalError::MissingOperand(5)),
+            ("(5 km to mi)", EvalError::UnknownSymbol(6))
+        ];
+        for item in expr_and_err {
+            let result = parse::<f64>(item.0, &ctx).and_then(|tree| evaluate(&tree, &ctx));
+            assert_eq!(result.unwrap_err(), item.1, "{}", item.0);
+            assert_eq!(evaluate_infix_expr::<f64>(item.0, &ctx).unwrap_err(), item.1, "{}", item.0);
+        }
+    }
+
+    #[test]
+    fn negation_conventions() {
+        let mut ctx = Context::new();
+        let tree = parse::<f64>("-2^2", &ctx).unwrap();
+        assert_eq!(evaluate(&tree, &ctx).unwrap(), -4.0);
+        ctx.negation = NegationConvention::Calculator;
+        let tree = parse::<f64>("-2^2", &ctx).unwrap();
+        assert_eq!(evaluate(&tree, &ctx).unwrap(), 4.0);
+        // printed text gives the same tree under either convention
+        assert_eq!(tree.to_string(), "(-2)^2");
+        let reparsed = parse::<f64>(&tree.to_string(), &Context::new()).unwrap();
+        assert_eq!(evaluate(&reparsed, &ctx).unwrap(), 4.0);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,229 @@
+/// Units of measurement and the physical dimensions they measure.
+/// Quantities are worked out in base units (e.g metres and seconds)
+/// and only converted into other units when asked to (e.g "5 km to mi").
+
+use std::fmt;
+use crate::numeric::Numeric;
+
+// Symbols of the base units, in the order of Dimension's exponents.
+// Money is measured in the base currency of the exchange rates,
+// which stands in for the ¤ sign when shown.
+const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "bit", "¤"];
+
+/// What a quantity measures, as the power of each base unit
+/// (mass, length, time, current, temperature, data size and money)
+/// e.g speeds are m^1×s^-1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimension([i32; 7]);
+
+impl Dimension {
+    // Dimension of plain numbers
+    pub const NONE: Dimension = Dimension([0; 7]);
+    // Dimension of amounts of money
+    pub const MONEY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1]);
+
+    pub fn is_none(&self) -> bool {
+        return *self == Dimension::NONE;
+    }
+
+    /// Whether the dimension includes money e.g for prices per kg
+    pub fn has_money(&self) -> bool {
+        return self.0[6] != 0;
+    }
+
+    /// Dimension of the product of two quantities e.g m×m = m^2
+    pub fn multiply(&self, other: &Dimension) -> Dimension {
+        let mut exponents = self.0;
+        for (exponent, other) in exponents.iter_mut().zip(other.0) {
+            *exponent += other;
+        }
+        return Dimension(exponents);
+    }
+
+    /// Dimension of the quotient of two quantities e.g m÷s
+    pub fn divide(&self, other: &Dimension) -> Dimension {
+        return self.multiply(&other.power(-1));
+    }
+
+    /// Dimension of a quantity raised to a whole power
+    pub fn power(&self, exponent: i32) -> Dimension {
+        return Dimension(self.0.map(|power| power * exponent));
+    }
+
+    /// Dimension of a quantity's nth root e.g √(m^2) = m.
+    ///
+    /// Returns None if the root has fractional powers (e.g √m).
+    pub fn root(&self, n: i32) -> Option<Dimension> {
+        if self.0.iter().any(|power| power % n != 0) {
+            return None;
+        }
+        return Some(Dimension(self.0.map(|power| power / n)));
+    }
+}
+
+impl fmt::Display for Dimension {
+    // Shown in base units so the text can be read back as an
+    // expression e.g "kg×m^2×s^-2". Negative powers come last.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let positives = BASE_UNITS.iter().zip(self.0).filter(|(_, power)| *power > 0);
+        let negatives = BASE_UNITS.iter().zip(self.0).filter(|(_, power)| *power < 0);
+        let parts: Vec<String> = positives.chain(negatives)
+            .map(|(unit, power)| match power {
+                1 => String::from(*unit),
+                _ => format!("{}^{}", unit, power)
+            })
+            .collect();
+        return write!(f, "{}", parts.join("×"));
+    }
+}
+
+/// Gives a dimension from the powers of
+/// mass, length, time, current, temperature and data size
+const fn dimension(mass: i32, length: i32, time: i32, current: i32, temperature: i32, data: i32) -> Dimension {
+    return Dimension([mass, length, time, current, temperature, data, 0]);
+}
+
+const MASS: Dimension = dimension(1, 0, 0, 0, 0, 0);
+const LENGTH: Dimension = dimension(0, 1, 0, 0, 0, 0);
+const TIME: Dimension = dimension(0, 0, 1, 0, 0, 0);
+const CURRENT: Dimension = dimension(0, 0, 0, 1, 0, 0);
+const TEMPERATURE: Dimension = dimension(0, 0, 0, 0, 1, 0);
+const DATA: Dimension = dimension(0, 0, 0, 0, 0, 1);
+const AREA: Dimension = dimension(0, 2, 0, 0, 0, 0);
+const VOLUME: Dimension = dimension(0, 3, 0, 0, 0, 0);
+const SPEED: Dimension = dimension(0, 1, -1, 0, 0, 0);
+const FREQUENCY: Dimension = dimension(0, 0, -1, 0, 0, 0);
+const FORCE: Dimension = dimension(1, 1, -2, 0, 0, 0);
+const ENERGY: Dimension = dimension(1, 2, -2, 0, 0, 0);
+const POWER: Dimension = dimension(1, 2, -3, 0, 0, 0);
+const PRESSURE: Dimension = dimension(1, -1, -2, 0, 0, 0);
+
+/// A unit that quantities can be written in
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    pub name: &'static str,
+    pub dimension: Dimension,
+    // Size of the unit in base units. Given as an expression so
+    // each Numeric type can read it as exactly as it can.
+    pub scale: &'static str,
+    // Base units at the unit's zero, for temperature scales which
+    // don't start at absolute zero (e.g 273.15 for °C)
+    pub offset: Option<&'static str>
+}
+
+const fn unit(name: &'static str, dimension: Dimension, scale: &'static str) -> Unit {
+    return Unit{name, dimension, scale, offset: None};
+}
+
+// Inches are written "inch" as "in" converts between units
+pub const UNITS: [Unit; 62] = [
+    // lengths
+    unit("m", LENGTH, "1"),
+    unit("km", LENGTH, "1000"),
+    unit("cm", LENGTH, "0.01"),
+    unit("mm", LENGTH, "0.001"),
+    unit("um", LENGTH, "0.000001"),
+    unit("nm", LENGTH, "0.000000001"),
+    unit("mi", LENGTH, "1609.344"),
+    unit("yd", LENGTH, "0.9144"),
+    unit("ft", LENGTH, "0.3048"),
+    unit("inch", LENGTH, "0.0254"),
+    unit("nmi", LENGTH, "1852"),
+    // areas and volumes
+    unit("ha", AREA, "10000"),
+    unit("acre", AREA, "4046.8564224"),
+    unit("L", VOLUME, "0.001"),
+    unit("mL", VOLUME, "0.000001"),
+    unit("gal", VOLUME, "0.003785411784"),
+    // masses
+    unit("kg", MASS, "1"),
+    unit("g", MASS, "0.001"),
+    unit("mg", MASS, "0.000001"),
+    unit("t", MASS, "1000"),
+    unit("lb", MASS, "0.45359237"),
+    unit("oz", MASS, "0.028349523125"),
+    // times
+    unit("s", TIME, "1"),
+    unit("ms", TIME, "0.001"),
+    unit("min", TIME, "60"),
+    unit("h", TIME, "3600"),
+    unit("day", TIME, "86400"),
+    unit("wk", TIME, "604800"),
+    unit("yr", TIME, "31557600"),
+    // currents
+    unit("A", CURRENT, "1"),
+    unit("mA", CURRENT, "0.001"),
+    // temperatures
+    unit("K", TEMPERATURE, "1"),
+    Unit{name: "°C", dimension: TEMPERATURE, scale: "1", offset: Some("273.15")},
+    Unit{name: "degC", dimension: TEMPERATURE, scale: "1", offset: Some("273.15")},
+    Unit{name: "°F", dimension: TEMPERATURE, scale: "5÷9", offset: Some("459.67×5÷9")},
+    Unit{name: "degF", dimension: TEMPERATURE, scale: "5÷9", offset: Some("459.67×5÷9")},
+    // data sizes
+    unit("bit", DATA, "1"),
+    unit("B", DATA, "8"),
+    unit("kB", DATA, "8000"),
+    unit("MB", DATA, "8000000"),
+    unit("GB", DATA, "8000000000"),
+    unit("TB", DATA, "8000000000000"),
+    unit("KiB", DATA, "8192"),
+    unit("MiB", DATA, "8388608"),
+    unit("GiB", DATA, "8589934592"),
+    unit("TiB", DATA, "8796093022208"),
+    // speeds and frequencies
+    unit("mph", SPEED, "0.44704"),
+    unit("kph", SPEED, "1÷3.6"),
+    unit("Hz", FREQUENCY, "1"),
+    unit("kHz", FREQUENCY, "1000"),
+    unit("MHz", FREQUENCY, "1000000"),
+    unit("GHz", FREQUENCY, "1000000000"),
+    // forces, energies, powers and pressures
+    unit("N", FORCE, "1"),
+    unit("J", ENERGY, "1"),
+    unit("kJ", ENERGY, "1000"),
+    unit("cal", ENERGY, "4.184"),
+    unit("kcal", ENERGY, "4184"),
+    unit("kWh", ENERGY, "3600000"),
+    unit("W", POWER, "1"),
+    unit("kW", POWER, "1000"),
+    unit("Pa", PRESSURE, "1"),
+    unit("bar", PRESSURE, "100000")
+];
+
+/// Finds the unit with the given name.
+///
+/// Returns None if there is no such unit.
+pub fn find_unit(name: &str) -> Option<&'static Unit> {
+    return UNITS.iter().find(|unit| unit.name == name);
+}
+
+/// A number along with the dimension of what it measures.
+/// Values are in base units unless converted into another unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantity<N: Numeric> {
+    pub value: N,
+    pub dimension: Dimension,
+    // Unit the value was converted into e.g "mi" for "5 km to mi"
+    pub unit: Option<String>
+}
+
+impl<N: Numeric> Quantity<N> {
+    /// A plain number, without any units
+    pub fn number(value: N) -> Self {
+        return Quantity{value, dimension: Dimension::NONE, unit: None};
+    }
+}
+
+impl<N: Numeric> fmt::Display for Quantity<N> {
+    // Shown with its units after it e.g "3.5 km÷h", which can
+    // be read back as an expression
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(unit) = &self.unit {
+            return write!(f, "{} {}", self.value, unit);
+        }
+        if self.dimension.is_none() {
+            return write!(f, "{}", self.value);
+        }
+        return write!(f, "{} {}", self.value, self.dimension);
+    }
+}
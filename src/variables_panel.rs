@@ -0,0 +1,47 @@
+/// The variables panel of the calculator
+
+use std::collections::BTreeMap;
+use eframe::egui;
+use crate::expression_evaluate::UserFunction;
+const FONT_SIZE: f32 = 24.0;
+const HEADING_FONT_SIZE: f32 = 16.0;
+
+// Action taken on a variable or function in the panel
+pub enum VariableAction {
+    Insert(String),
+    Remove(String)
+}
+
+/// Displays each variable and its value, followed by each user defined
+/// function, in a list format.
+/// Clicking an entry inserts its name (with an open bracket for functions)
+/// and "✖" removes it.
+/// 
+/// Returns the action taken on a variable or function (if any).
+pub fn show_variables(ui: &mut egui::Ui, variables: &BTreeMap<String, String>, functions: &BTreeMap<String, UserFunction>) -> Option<VariableAction> {
+    let mut action: Option<VariableAction> = None;
+    ui.label(egui::RichText::new("Variables").size(HEADING_FONT_SIZE).weak());
+    ui.separator();
+    // Each entry's name, how it is shown and the text inserted when clicked
+    let entries = variables.iter()
+        .map(|(name, value)| (name, format!("{} = {}", name, value), name.clone()))
+        .chain(functions.iter()
+            .map(|(name, function)| (name, format!("{}{}", name, function), format!("{}(", name))));
+    // Set vertical scrollable panel
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (name, shown, inserted) in entries {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").clicked() {
+                    action = Some(VariableAction::Remove(name.clone()));
+                }
+                if ui.add(egui::Label::new(egui::RichText::new(shown).size(FONT_SIZE))
+                    .sense(egui::Sense::click()))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .clicked() {
+                    action = Some(VariableAction::Insert(inserted));
+                }
+            });
+        }
+    });
+    return action;
+}
@@ -0,0 +1,45 @@
+/// The working panel of the calculator, showing how an answer was
+/// reached through postfix notation (aka Reverse Polish Notation)
+
+use eframe::egui;
+use crate::expression_evaluate::Working;
+const FONT_SIZE: f32 = 18.0;
+const HEADING_FONT_SIZE: f32 = 16.0;
+
+/// Displays the postfix form of the evaluated expression, then each
+/// token read along with the working stack after it (top of the stack
+/// on the right), then the final answer.
+pub fn show_working(ui: &mut egui::Ui, working: &Option<Working>) {
+    ui.label(egui::RichText::new("Working").size(HEADING_FONT_SIZE).weak());
+    ui.separator();
+    let working = match working {
+        Some(working) => working,
+        None => {
+            ui.label(egui::RichText::new("Evaluate an expression to see its working").weak());
+            return;
+        }
+    };
+    // Set vertical scrollable panel
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label(egui::RichText::new(&working.expression).size(FONT_SIZE));
+        ui.label(egui::RichText::new("Postfix").size(HEADING_FONT_SIZE).weak());
+        ui.label(egui::RichText::new(working.postfix.join(" ")).size(FONT_SIZE).monospace());
+        ui.separator();
+        // Each token and the stack after reading it
+        egui::Grid::new("working_steps").striped(true).show(ui, |ui| {
+            ui.label(egui::RichText::new("Read").weak());
+            ui.label(egui::RichText::new("Stack").weak());
+            ui.end_row();
+            for (token, stack) in working.steps.iter() {
+                ui.label(egui::RichText::new(token).size(FONT_SIZE).monospace());
+                ui.label(egui::RichText::new(stack.join("  ")).size(FONT_SIZE).monospace());
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        match &working.result {
+            Ok(answer) => ui.label(egui::RichText::new(format!("= {}", answer)).size(FONT_SIZE).strong()),
+            Err(error) => ui.label(egui::RichText::new(format!("Error: {}", error)).size(FONT_SIZE).color(egui::Color32::RED))
+        };
+    });
+}